@@ -14,6 +14,8 @@
 
 use crate::{ffi, Options};
 
+use std::time::Duration;
+
 /// The name of the default column family.
 ///
 /// The column family with this name is created implicitly whenever column
@@ -26,6 +28,7 @@ pub const DEFAULT_COLUMN_FAMILY_NAME: &str = "default";
 pub struct ColumnFamilyDescriptor {
     pub(crate) name: String,
     pub(crate) options: Options,
+    pub(crate) ttl: Option<Duration>,
 }
 
 impl ColumnFamilyDescriptor {
@@ -37,6 +40,23 @@ impl ColumnFamilyDescriptor {
         ColumnFamilyDescriptor {
             name: name.into(),
             options,
+            ttl: None,
+        }
+    }
+
+    /// Create a new column family descriptor with the specified name, options and Time to Live.
+    ///
+    /// The TTL is only used when the database is opened with
+    /// [`DB::open_cf_descriptors_with_ttl`](crate::DB::open_cf_descriptors_with_ttl), and
+    /// overrides the TTL given to it for this column family.
+    pub fn new_with_ttl<S>(name: S, options: Options, ttl: Duration) -> Self
+    where
+        S: Into<String>,
+    {
+        ColumnFamilyDescriptor {
+            name: name.into(),
+            options,
+            ttl: Some(ttl),
         }
     }
 }
@@ -16,10 +16,11 @@
 use crate::{
     ffi,
     ffi_util::{from_cstr, opt_bytes_to_ptr, raw_data, to_cpath},
+    properties::{self, parse_level_stats, BackgroundWorkStats, LevelStats},
     ColumnFamily, ColumnFamilyDescriptor, CompactOptions, DBIterator, DBPinnableSlice,
     DBRawIterator, DBWALIterator, Direction, Error, FlushOptions, IngestExternalFileOptions,
-    IteratorMode, Options, ReadOptions, Snapshot, WriteBatch, WriteOptions,
-    DEFAULT_COLUMN_FAMILY_NAME,
+    IteratorMode, Options, OwnedDBIterator, OwnedSnapshot, ReadOptions, Snapshot,
+    WalRecordIterator, WriteBatch, WriteOptions, DEFAULT_COLUMN_FAMILY_NAME,
 };
 
 use libc::{self, c_char, c_int, c_uchar, c_void, size_t};
@@ -27,11 +28,13 @@ use std::collections::BTreeMap;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::fs;
+use std::ops::RangeBounds;
 use std::path::Path;
 use std::path::PathBuf;
 use std::ptr;
 use std::slice;
 use std::str;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// A RocksDB database.
@@ -41,6 +44,7 @@ pub struct DB {
     pub(crate) inner: *mut ffi::rocksdb_t,
     cfs: BTreeMap<String, ColumnFamily>,
     path: PathBuf,
+    with_ttl: bool,
 }
 
 // Safety note: auto-implementing Send on most db-related types is prevented by the inner FFI
@@ -97,17 +101,7 @@ impl DB {
         path: P,
         ttl: Duration,
     ) -> Result<DB, Error> {
-        let c_path = to_cpath(&path)?;
-        let db = DB::open_raw(opts, &c_path, &AccessType::WithTTL { ttl })?;
-        if db.is_null() {
-            return Err(Error::new("Could not initialize database.".to_owned()));
-        }
-
-        Ok(DB {
-            inner: db,
-            cfs: BTreeMap::new(),
-            path: path.as_ref().to_path_buf(),
-        })
+        DB::open_cf_descriptors_with_ttl(opts, path, None, ttl)
     }
 
     /// Opens a database with the given database options and column family names.
@@ -187,6 +181,58 @@ impl DB {
         DB::open_cf_descriptors_internal(opts, path, cfs, &AccessType::ReadWrite)
     }
 
+    /// Opens a database with the given database options and column family descriptors, using a
+    /// Time to Live compaction filter.
+    ///
+    /// Every column family uses its own TTL when the descriptor was created with
+    /// [`ColumnFamilyDescriptor::new_with_ttl`], and `ttl` otherwise. This includes the default
+    /// column family when it is not part of `cfs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+    /// use std::time::Duration;
+    ///
+    /// let path = "_path_for_rocksdb_storage_with_ttl_cfs";
+    /// {
+    ///     let mut opts = Options::default();
+    ///     opts.create_if_missing(true);
+    ///     opts.create_missing_column_families(true);
+    ///
+    ///     let sessions = ColumnFamilyDescriptor::new_with_ttl(
+    ///         "sessions",
+    ///         Options::default(),
+    ///         Duration::from_secs(60 * 60),
+    ///     );
+    ///     let audit = ColumnFamilyDescriptor::new_with_ttl(
+    ///         "audit",
+    ///         Options::default(),
+    ///         Duration::from_secs(90 * 24 * 60 * 60),
+    ///     );
+    ///     let db = DB::open_cf_descriptors_with_ttl(
+    ///         &opts,
+    ///         path,
+    ///         vec![sessions, audit],
+    ///         Duration::from_secs(0),
+    ///     )
+    ///     .unwrap();
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn open_cf_descriptors_with_ttl<P, I>(
+        opts: &Options,
+        path: P,
+        cfs: I,
+        ttl: Duration,
+    ) -> Result<DB, Error>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = ColumnFamilyDescriptor>,
+    {
+        DB::open_cf_descriptors_internal(opts, path, cfs, &AccessType::WithTTL { ttl })
+    }
+
     /// Internal implementation for opening RocksDB.
     fn open_cf_descriptors_internal<P, I>(
         opts: &Options,
@@ -221,6 +267,7 @@ impl DB {
                 cfs_v.push(ColumnFamilyDescriptor {
                     name: String::from(DEFAULT_COLUMN_FAMILY_NAME),
                     options: Options::default(),
+                    ttl: None,
                 });
             }
             // We need to store our CStrings in an intermediate vector
@@ -270,6 +317,7 @@ impl DB {
             inner: db,
             cfs: cf_map,
             path: path.as_ref().to_path_buf(),
+            with_ttl: matches!(access_type, AccessType::WithTTL { .. }),
         })
     }
 
@@ -348,7 +396,22 @@ impl DB {
                         cfhandles.as_mut_ptr(),
                     ))
                 }
-                _ => return Err(Error::new("Unsupported access type".to_owned())),
+                AccessType::WithTTL { ttl } => {
+                    let ttls: Vec<_> = cfs_v
+                        .iter()
+                        .map(|cf| cf.ttl.unwrap_or(ttl).as_secs() as c_int)
+                        .collect();
+
+                    ffi_try!(ffi::rocksdb_open_column_families_with_ttl(
+                        opts.inner,
+                        cpath.as_ptr(),
+                        cfs_v.len() as c_int,
+                        cfnames.as_ptr(),
+                        cfopts.as_ptr(),
+                        cfhandles.as_mut_ptr(),
+                        ttls.as_ptr(),
+                    ))
+                }
             }
         };
         Ok(db)
@@ -564,6 +627,81 @@ impl DB {
         self.get_pinned_cf_opt(cf, key, &ReadOptions::default())
     }
 
+    /// Returns `false` if the key definitely doesn't exist in the database, using default
+    /// read options.
+    ///
+    /// This is a cheap check which reads only the memtables and the block cache, so `true`
+    /// may be returned for a key which doesn't exist.
+    pub fn key_may_exist<K: AsRef<[u8]>>(&self, key: K) -> bool {
+        self.key_may_exist_opt(key, &ReadOptions::default()).0
+    }
+
+    /// Returns `false` if the key definitely doesn't exist in the database, using the given
+    /// read options.
+    ///
+    /// If the value could be found without doing IO, for instance in a memtable, it is
+    /// returned as well. See [`key_may_exist`](#method.key_may_exist).
+    pub fn key_may_exist_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> (bool, Option<Vec<u8>>) {
+        let key = key.as_ref();
+        let mut value: *mut c_char = ptr::null_mut();
+        let mut value_len: size_t = 0;
+        let mut value_found: c_uchar = 0;
+        let may_exist = unsafe {
+            ffi::rocksdb_key_may_exist(
+                self.inner,
+                readopts.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut value,
+                &mut value_len,
+                ptr::null(),
+                0,
+                &mut value_found,
+            )
+        };
+        (may_exist != 0, found_value(value_found, value, value_len))
+    }
+
+    /// Returns `false` if the key definitely doesn't exist in the given column family, using
+    /// default read options. See [`key_may_exist`](#method.key_may_exist).
+    pub fn key_may_exist_cf<K: AsRef<[u8]>>(&self, cf: &ColumnFamily, key: K) -> bool {
+        self.key_may_exist_cf_opt(cf, key, &ReadOptions::default())
+            .0
+    }
+
+    /// Returns `false` if the key definitely doesn't exist in the given column family, using
+    /// the given read options. See [`key_may_exist_opt`](#method.key_may_exist_opt).
+    pub fn key_may_exist_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        readopts: &ReadOptions,
+    ) -> (bool, Option<Vec<u8>>) {
+        let key = key.as_ref();
+        let mut value: *mut c_char = ptr::null_mut();
+        let mut value_len: size_t = 0;
+        let mut value_found: c_uchar = 0;
+        let may_exist = unsafe {
+            ffi::rocksdb_key_may_exist_cf(
+                self.inner,
+                readopts.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+                &mut value,
+                &mut value_len,
+                ptr::null(),
+                0,
+                &mut value_found,
+            )
+        };
+        (may_exist != 0, found_value(value_found, value, value_len))
+    }
+
     /// Return the values associated with the given keys.
     pub fn multi_get<K, I>(&self, keys: I) -> Result<Vec<Vec<u8>>, Error>
     where
@@ -681,6 +819,44 @@ impl DB {
         Ok(())
     }
 
+    /// Creates a column family with a Time to Live compaction filter.
+    ///
+    /// Only available for databases opened with [`open_with_ttl`](#method.open_with_ttl) or
+    /// [`open_cf_descriptors_with_ttl`](#method.open_cf_descriptors_with_ttl).
+    pub fn create_cf_with_ttl<N: AsRef<str>>(
+        &mut self,
+        name: N,
+        opts: &Options,
+        ttl: Duration,
+    ) -> Result<(), Error> {
+        if !self.with_ttl {
+            return Err(Error::new(
+                "Column families with TTL can only be created in a database opened with TTL"
+                    .to_owned(),
+            ));
+        }
+
+        let cf_name = if let Ok(c) = CString::new(name.as_ref().as_bytes()) {
+            c
+        } else {
+            return Err(Error::new(
+                "Failed to convert path to CString when creating cf".to_owned(),
+            ));
+        };
+        unsafe {
+            let inner = ffi_try!(ffi::rocksdb_create_column_family_with_ttl(
+                self.inner,
+                opts.inner,
+                cf_name.as_ptr(),
+                ttl.as_secs() as c_int,
+            ));
+
+            self.cfs
+                .insert(name.as_ref().to_string(), ColumnFamily { inner });
+        };
+        Ok(())
+    }
+
     pub fn drop_cf(&mut self, name: &str) -> Result<(), Error> {
         if let Some(cf) = self.cfs.remove(name) {
             unsafe {
@@ -721,6 +897,64 @@ impl DB {
         DBIterator::new_cf(self, cf_handle, readopts, mode)
     }
 
+    /// Opens an iterator which keeps a reference to the database instead of borrowing it, using
+    /// the default read options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{DB, IteratorMode, Options};
+    /// use std::sync::Arc;
+    ///
+    /// let path = "_path_for_rocksdb_storage_iterator_owned";
+    /// {
+    ///     let db = Arc::new(DB::open_default(path).unwrap());
+    ///     db.put(b"k1", b"v1").unwrap();
+    ///     let iter = db.iterator_owned(IteratorMode::Start);
+    ///     let handle = std::thread::spawn(move || iter.count());
+    ///     assert_eq!(handle.join().unwrap(), 1);
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn iterator_owned(self: &Arc<Self>, mode: IteratorMode) -> OwnedDBIterator {
+        let readopts = ReadOptions::default();
+        self.iterator_opt_owned(mode, readopts)
+    }
+
+    /// Opens an iterator which keeps a reference to the database instead of borrowing it, using
+    /// the given read options.
+    pub fn iterator_opt_owned(
+        self: &Arc<Self>,
+        mode: IteratorMode,
+        readopts: ReadOptions,
+    ) -> OwnedDBIterator {
+        let iter = DBIterator::new(self, readopts, mode);
+        OwnedDBIterator::new(iter, None, Arc::clone(self))
+    }
+
+    /// Opens an iterator over the given column family which keeps a reference to the database
+    /// instead of borrowing it, using the default read options.
+    pub fn iterator_cf_owned(
+        self: &Arc<Self>,
+        cf_handle: &ColumnFamily,
+        mode: IteratorMode,
+    ) -> OwnedDBIterator {
+        let readopts = ReadOptions::default();
+        self.iterator_cf_opt_owned(cf_handle, readopts, mode)
+    }
+
+    /// Opens an iterator over the given column family which keeps a reference to the database
+    /// instead of borrowing it, using the given read options.
+    pub fn iterator_cf_opt_owned(
+        self: &Arc<Self>,
+        cf_handle: &ColumnFamily,
+        readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> OwnedDBIterator {
+        let iter = DBIterator::new_cf(self, cf_handle, readopts, mode);
+        OwnedDBIterator::new(iter, None, Arc::clone(self))
+    }
+
     /// Opens an iterator with `set_total_order_seek` enabled.
     /// This must be used to iterate across prefixes when `set_memtable_factory` has been called
     /// with a Hash-based implementation.
@@ -774,6 +1008,111 @@ impl DB {
         )
     }
 
+    /// Opens an iterator over exactly the keys starting with `prefix`, in the given direction.
+    ///
+    /// Unlike [`prefix_iterator`](#method.prefix_iterator), this doesn't depend on a prefix
+    /// extractor: the iterate bounds are set from `prefix` (see
+    /// [`ReadOptions::set_iterate_prefix`]) and the iterator uses total order seek, so prefix
+    /// bloom filters are not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{DB, Direction, Options};
+    ///
+    /// let path = "_path_for_rocksdb_storage_prefix_scan";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     db.put(b"user:1", b"alice").unwrap();
+    ///     db.put(b"user:2", b"bob").unwrap();
+    ///     db.put(b"users", b"2").unwrap();
+    ///
+    ///     let mut iter = db.prefix_scan(b"user:", Direction::Reverse);
+    ///     assert_eq!(iter.next().unwrap().0.as_ref(), b"user:2");
+    ///     assert_eq!(iter.next().unwrap().0.as_ref(), b"user:1");
+    ///     assert!(iter.next().is_none());
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn prefix_scan<'a: 'b, 'b, P: AsRef<[u8]>>(
+        &'a self,
+        prefix: P,
+        direction: Direction,
+    ) -> DBIterator<'b> {
+        DBIterator::new(self, prefix_scan_opts(prefix), direction.start_mode())
+    }
+
+    /// Opens an iterator over exactly the keys starting with `prefix` in the given column
+    /// family, in the given direction.
+    ///
+    /// See [`prefix_scan`](#method.prefix_scan).
+    pub fn prefix_scan_cf<'a: 'b, 'b, P: AsRef<[u8]>>(
+        &'a self,
+        cf_handle: &ColumnFamily,
+        prefix: P,
+        direction: Direction,
+    ) -> DBIterator<'b> {
+        DBIterator::new_cf(
+            self,
+            cf_handle,
+            prefix_scan_opts(prefix),
+            direction.start_mode(),
+        )
+    }
+
+    /// Opens an iterator over the keys in `range`, in the given direction.
+    ///
+    /// The range is translated into iterate bounds of the read options, see
+    /// [`ReadOptions::set_iterate_range`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{DB, Direction, Options};
+    ///
+    /// let path = "_path_for_rocksdb_storage_range_iterator";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     for (key, value) in db.range_iterator(&b"a"[..]..&b"c"[..], Direction::Reverse) {
+    ///         println!("Saw {:?} {:?}", key, value);
+    ///     }
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn range_iterator<'a: 'b, 'b, R, K>(
+        &'a self,
+        range: R,
+        direction: Direction,
+    ) -> DBIterator<'b>
+    where
+        R: RangeBounds<K>,
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_range(range);
+        DBIterator::new(self, opts, direction.start_mode())
+    }
+
+    /// Opens an iterator over the keys in `range` of the given column family, in the given
+    /// direction.
+    ///
+    /// The range is translated into iterate bounds of the read options, see
+    /// [`ReadOptions::set_iterate_range`].
+    pub fn range_iterator_cf<'a: 'b, 'b, R, K>(
+        &'a self,
+        cf_handle: &ColumnFamily,
+        range: R,
+        direction: Direction,
+    ) -> DBIterator<'b>
+    where
+        R: RangeBounds<K>,
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let mut opts = ReadOptions::default();
+        opts.set_iterate_range(range);
+        DBIterator::new_cf(self, cf_handle, opts, direction.start_mode())
+    }
+
     /// Opens a raw iterator over the database, using the default read options
     pub fn raw_iterator<'a: 'b, 'b>(&'a self) -> DBRawIterator<'b> {
         let opts = ReadOptions::default();
@@ -804,6 +1143,11 @@ impl DB {
         Snapshot::new(self)
     }
 
+    /// Creates a snapshot which keeps a reference to the database instead of borrowing it.
+    pub fn snapshot_owned(self: &Arc<Self>) -> OwnedSnapshot {
+        OwnedSnapshot::new(Arc::clone(self))
+    }
+
     pub fn put_opt<K, V>(&self, key: K, value: V, writeopts: &WriteOptions) -> Result<(), Error>
     where
         K: AsRef<[u8]>,
@@ -1005,6 +1349,53 @@ impl DB {
         self.delete_cf_opt(cf, key.as_ref(), &WriteOptions::default())
     }
 
+    /// Inserts a value under a key, where the key and the value are each the concatenation
+    /// of the given parts, without first concatenating them.
+    pub fn putv(&self, key: &[&[u8]], value: &[&[u8]]) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        batch.putv(key, value);
+        self.write(batch)
+    }
+
+    pub fn putv_cf(&self, cf: &ColumnFamily, key: &[&[u8]], value: &[&[u8]]) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        batch.putv_cf(cf, key, value);
+        self.write(batch)
+    }
+
+    /// Merges a value under a key, where the key and the value are each the concatenation
+    /// of the given parts, without first concatenating them.
+    pub fn mergev(&self, key: &[&[u8]], value: &[&[u8]]) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        batch.mergev(key, value);
+        self.write(batch)
+    }
+
+    pub fn mergev_cf(
+        &self,
+        cf: &ColumnFamily,
+        key: &[&[u8]],
+        value: &[&[u8]],
+    ) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        batch.mergev_cf(cf, key, value);
+        self.write(batch)
+    }
+
+    /// Removes the entry for the key which is the concatenation of the given parts, without
+    /// first concatenating them.
+    pub fn deletev(&self, key: &[&[u8]]) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        batch.deletev(key);
+        self.write(batch)
+    }
+
+    pub fn deletev_cf(&self, cf: &ColumnFamily, key: &[&[u8]]) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        batch.deletev_cf(cf, key);
+        self.write(batch)
+    }
+
     /// Removes the database entries in the range `["from", "to")` using default write options.
     pub fn delete_range_cf<K: AsRef<[u8]>>(
         &self,
@@ -1253,6 +1644,42 @@ impl DB {
         }
     }
 
+    /// Retrieves the number of files and size of each level, parsed from the
+    /// `rocksdb.levelstats` property.
+    pub fn level_stats(&self) -> Result<Option<Vec<LevelStats>>, Error> {
+        match self.property_value(properties::LEVELSTATS)? {
+            Some(value) => parse_level_stats(&value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieves the number of files and size of each level of a specific column family,
+    /// parsed from the `rocksdb.levelstats` property.
+    pub fn level_stats_cf(&self, cf: &ColumnFamily) -> Result<Option<Vec<LevelStats>>, Error> {
+        match self.property_value_cf(cf, properties::LEVELSTATS)? {
+            Some(value) => parse_level_stats(&value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieves the pending and running flushes and compactions, e.g. to find out what the
+    /// background threads are doing when writes stall.
+    ///
+    /// The pending work is the one of the default column family, while the running jobs and
+    /// write stalls are those of the whole database.
+    pub fn background_work_stats(&self) -> Result<BackgroundWorkStats, Error> {
+        properties::background_work_stats(|name| self.property_int_value(name))
+    }
+
+    /// Retrieves the pending flushes and compactions of a specific column family, along with
+    /// the running ones of the whole database.
+    pub fn background_work_stats_cf(
+        &self,
+        cf: &ColumnFamily,
+    ) -> Result<BackgroundWorkStats, Error> {
+        properties::background_work_stats(|name| self.property_int_value_cf(cf, name))
+    }
+
     /// The sequence number of the most recent transaction.
     pub fn latest_sequence_number(&self) -> u64 {
         unsafe { ffi::rocksdb_get_latest_sequence_number(self.inner) }
@@ -1279,6 +1706,83 @@ impl DB {
         }
     }
 
+    /// Replays the writes recorded in the WAL of this database into `target`, up to and
+    /// including the sequence number `until_seq`.
+    ///
+    /// `target` must be a copy of this database restored from a checkpoint or a backup. The
+    /// write batches following its latest sequence number are read with
+    /// `get_updates_since()` and written to it, so the WAL files covering them must still be
+    /// available, for instance by keeping archived WAL files with `set_wal_ttl_seconds` or
+    /// `set_wal_size_limit_mb`. Batches are applied atomically, so a batch spanning past
+    /// `until_seq` is not applied. Column families are matched by id, which is preserved by
+    /// checkpoints and backups.
+    ///
+    /// Returns the latest sequence number of `target` after the replay.
+    pub fn replay_wal_into(&self, target: &DB, until_seq: u64) -> Result<u64, Error> {
+        let until_seq = until_seq.min(self.latest_sequence_number());
+        let mut next_seq = target.latest_sequence_number() + 1;
+        // This includes an empty source database, whose WAL can't be read from sequence 1.
+        if next_seq > until_seq {
+            return Ok(target.latest_sequence_number());
+        }
+        let mut updates = self.get_updates_since(next_seq)?;
+        // Unlike `next()`, `current()` returns the batch containing `next_seq`.
+        let mut update = updates.current();
+        while let Some((seq, batch)) = update {
+            if seq > next_seq {
+                return Err(Error::new(format!(
+                    "WAL is missing sequence numbers {} to {}",
+                    next_seq,
+                    seq - 1
+                )));
+            }
+            if seq < next_seq {
+                return Err(Error::new(format!(
+                    "Sequence number {} is in the middle of a WAL batch",
+                    next_seq
+                )));
+            }
+            let end_seq = seq + batch.len() as u64;
+            if end_seq > until_seq + 1 {
+                break;
+            }
+            target.write(batch)?;
+            next_seq = end_seq;
+            update = updates.next();
+        }
+        if next_seq <= until_seq {
+            updates.status()?;
+        }
+        Ok(target.latest_sequence_number())
+    }
+
+    /// Iterate over the decoded write batches since a given sequence.
+    ///
+    /// This is similar to `get_updates_since()`, except that each batch is decoded into a
+    /// [`WalRecord`](struct.WalRecord.html) and that the first one is the batch containing
+    /// `seq_number`, so `wal_records_since(0)` returns every batch still in the WAL. The
+    /// iterator ends once the latest write has been read.
+    pub fn wal_records_since(&self, seq_number: u64) -> Result<WalRecordIterator, Error> {
+        let iter = self.get_updates_since(seq_number)?;
+        Ok(WalRecordIterator::new(self, Some(iter), seq_number, None))
+    }
+
+    /// Iterate over the decoded write batches since a given sequence, waiting for new writes.
+    ///
+    /// Instead of ending once the latest write has been read, the iterator checks for new
+    /// writes every `poll_interval` and blocks until there are some. Errors reading the WAL
+    /// are returned as items, and the iterator waits for `poll_interval` before trying again.
+    ///
+    /// Sequence numbers whose WAL files have already been deleted are not an error: like
+    /// `get_updates_since()`, the iterator starts at the earliest batch left in the WAL.
+    pub fn tail_wal_records_since(
+        &self,
+        seq_number: u64,
+        poll_interval: Duration,
+    ) -> WalRecordIterator {
+        WalRecordIterator::new(self, None, seq_number, Some(poll_interval))
+    }
+
     /// Tries to catch up with the primary by reading as much as possible from the
     /// log files.
     pub fn try_catch_up_with_primary(&self) -> Result<(), Error> {
@@ -1420,6 +1924,32 @@ impl DB {
         }
     }
 
+    /// Prevents the deletion of obsolete files, so that the files of the database can be
+    /// copied while it is in use, e.g. those returned by `live_files`.
+    ///
+    /// Calls nest: file deletions are only enabled again once `enable_file_deletions` has been
+    /// called as many times, or once with `force`.
+    pub fn disable_file_deletions(&self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_disable_file_deletions(self.inner));
+        }
+        Ok(())
+    }
+
+    /// Allows the deletion of obsolete files again after `disable_file_deletions`.
+    ///
+    /// If `force` is true, file deletions are enabled regardless of how many times they have
+    /// been disabled.
+    pub fn enable_file_deletions(&self, force: bool) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_enable_file_deletions(
+                self.inner,
+                force as c_uchar
+            ));
+        }
+        Ok(())
+    }
+
     /// Delete sst files whose keys are entirely in the given range.
     ///
     /// Could leave some keys in the range which are in files which are not
@@ -1471,6 +2001,33 @@ impl DB {
             ffi::rocksdb_cancel_all_background_work(self.inner, wait as u8);
         }
     }
+
+    /// Flushes the memtables of all column families, stops the background work and closes
+    /// the database.
+    ///
+    /// Background work is stopped with `cancel_all_background_work(true)`: running
+    /// compactions are aborted, and `close` waits until the background jobs have ended.
+    ///
+    /// Dropping a `DB` closes it without reporting errors. Once `close` returns `Ok`, the
+    /// data written so far is persisted in SST files and no longer depends on the WAL. The
+    /// database is closed even if a flush fails, in which case the first error is returned.
+    ///
+    /// Errors of the final close itself, such as failing to sync or close the WAL, are still
+    /// not reported, since the C API closes the database without returning a status.
+    ///
+    /// Flushing is not supported by databases opened with `open_for_read_only`, so `close`
+    /// returns an error for these.
+    pub fn close(self) -> Result<(), Error> {
+        let flushopts = FlushOptions::default();
+        let result = self.flush_opt(&flushopts).and_then(|()| {
+            self.cfs
+                .iter()
+                .filter(|(name, _)| name.as_str() != DEFAULT_COLUMN_FAMILY_NAME)
+                .try_for_each(|(_, cf)| self.flush_cf_opt(cf, &flushopts))
+        });
+        self.cancel_all_background_work(true);
+        result
+    }
 }
 
 impl Drop for DB {
@@ -1509,6 +2066,14 @@ pub struct LiveFile {
     pub num_deletions: u64,
 }
 
+/// Read options for iterating over exactly the keys starting with `prefix`.
+pub(crate) fn prefix_scan_opts<P: AsRef<[u8]>>(prefix: P) -> ReadOptions {
+    let mut opts = ReadOptions::default();
+    opts.set_iterate_prefix(prefix);
+    opts.set_total_order_seek(true);
+    opts
+}
+
 fn convert_options(opts: &[(&str, &str)]) -> Result<Vec<(CString, CString)>, Error> {
     opts.iter()
         .map(|(name, value)| {
@@ -1525,11 +2090,23 @@ fn convert_options(opts: &[(&str, &str)]) -> Result<Vec<(CString, CString)>, Err
         .collect()
 }
 
+/// Takes ownership of the value returned by `rocksdb_key_may_exist` when it was found.
+fn found_value(value_found: c_uchar, value: *mut c_char, value_len: size_t) -> Option<Vec<u8>> {
+    if value_found == 0 || value.is_null() {
+        return None;
+    }
+    convert_values(vec![value], vec![value_len]).pop()
+}
+
 fn convert_values(values: Vec<*mut c_char>, values_sizes: Vec<usize>) -> Vec<Vec<u8>> {
     values
         .into_iter()
         .zip(values_sizes.into_iter())
         .map(|(v, s)| {
+            // Keys which were not found have a null value.
+            if v.is_null() {
+                return Vec::new();
+            }
             let value = unsafe { slice::from_raw_parts(v as *const u8, s) }.into();
             unsafe {
                 ffi::rocksdb_free(v as *mut c_void);
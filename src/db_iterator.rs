@@ -12,10 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{ffi, ColumnFamily, Error, ReadOptions, WriteBatch, DB};
+use crate::{ffi, ColumnFamily, Error, OwnedSnapshot, ReadOptions, WriteBatch, DB};
 use libc::{c_char, c_uchar, size_t};
 use std::marker::PhantomData;
+use std::ops::ControlFlow;
 use std::slice;
+use std::sync::Arc;
 
 /// An iterator over a database or column family, with specifiable
 /// ranges and direction.
@@ -376,6 +378,16 @@ pub enum Direction {
     Reverse,
 }
 
+impl Direction {
+    /// Returns the mode which starts iterating from the first key in this direction.
+    pub(crate) fn start_mode(self) -> IteratorMode<'static> {
+        match self {
+            Direction::Forward => IteratorMode::Start,
+            Direction::Reverse => IteratorMode::End,
+        }
+    }
+}
+
 pub type KVBytes = (Box<[u8]>, Box<[u8]>);
 
 pub enum IteratorMode<'a> {
@@ -442,12 +454,30 @@ impl<'a> DBIterator<'a> {
     pub fn status(&self) -> Result<(), Error> {
         self.raw.status()
     }
-}
 
-impl<'a> Iterator for DBIterator<'a> {
-    type Item = KVBytes;
-
-    fn next(&mut self) -> Option<KVBytes> {
+    /// Advances the iterator and returns the next key and value without copying them.
+    ///
+    /// The returned slices borrow the iterator, so they are only valid until it is advanced
+    /// again. Returns `None` when the end of the iteration is reached or an error is
+    /// encountered, see [`status`](DBIterator::status).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{DB, IteratorMode, Options};
+    ///
+    /// let path = "_path_for_rocksdb_storage_next_ref";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     let mut iter = db.iterator(IteratorMode::Start);
+    ///     while let Some((key, value)) = iter.next_ref() {
+    ///         println!("Saw {:?} {:?}", key, value);
+    ///     }
+    ///     iter.status().unwrap();
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn next_ref(&mut self) -> Option<(&[u8], &[u8])> {
         if !self.raw.valid() {
             return None;
         }
@@ -463,24 +493,218 @@ impl<'a> Iterator for DBIterator<'a> {
             }
         }
 
-        if self.raw.valid() {
-            // .key() and .value() only ever return None if valid == false, which we've just cheked
-            Some((
-                Box::from(self.raw.key().unwrap()),
-                Box::from(self.raw.value().unwrap()),
-            ))
-        } else {
-            None
+        // .key() and .value() only ever return None if valid == false
+        self.raw.key().zip(self.raw.value())
+    }
+
+    /// Calls `f` with every remaining key and value without copying them, until `f` returns
+    /// `ControlFlow::Break` or the end of the iteration is reached.
+    ///
+    /// Returns the error encountered by the underlying iterator, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{DB, IteratorMode, Options};
+    /// use std::ops::ControlFlow;
+    ///
+    /// let path = "_path_for_rocksdb_storage_for_each_ref";
+    /// {
+    ///     let db = DB::open_default(path).unwrap();
+    ///     let mut total_size = 0;
+    ///     db.iterator(IteratorMode::Start)
+    ///         .for_each_ref(|key, value| {
+    ///             total_size += key.len() + value.len();
+    ///             ControlFlow::Continue(())
+    ///         })
+    ///         .unwrap();
+    /// }
+    /// let _ = DB::destroy(&Options::default(), path);
+    /// ```
+    pub fn for_each_ref<F>(&mut self, mut f: F) -> Result<(), Error>
+    where
+        F: FnMut(&[u8], &[u8]) -> ControlFlow<()>,
+    {
+        while let Some((key, value)) = self.next_ref() {
+            if let ControlFlow::Break(()) = f(key, value) {
+                break;
+            }
+        }
+        self.status()
+    }
+
+    /// Converts this iterator into a [`DBFallibleIterator`], which yields `Result` items and
+    /// reports an error encountered by the underlying iterator as its final item.
+    pub fn into_fallible(self) -> DBFallibleIterator<'a> {
+        DBFallibleIterator {
+            inner: self,
+            done: false,
         }
     }
 }
 
+impl<'a> Iterator for DBIterator<'a> {
+    type Item = KVBytes;
+
+    fn next(&mut self) -> Option<KVBytes> {
+        self.next_ref()
+            .map(|(key, value)| (Box::from(key), Box::from(value)))
+    }
+}
+
 impl<'a> Into<DBRawIterator<'a>> for DBIterator<'a> {
     fn into(self) -> DBRawIterator<'a> {
         self.raw
     }
 }
 
+/// An iterator over a database or column family which yields `Result` items.
+///
+/// The standard `DBIterator` stops when the underlying iterator becomes invalid, so that an
+/// I/O error or a corruption encountered during the scan looks like the end of the data
+/// unless [`status`](DBIterator::status) is checked afterwards. This iterator checks the status
+/// itself and yields the error as its final item instead.
+///
+/// ```
+/// use rocksdb::{DB, IteratorMode, Options};
+///
+/// let path = "_path_for_rocksdb_storage_fallible_iterator";
+/// {
+///     let db = DB::open_default(path).unwrap();
+///     for item in db.iterator(IteratorMode::Start).into_fallible() {
+///         let (key, value) = item.unwrap();
+///         println!("Saw {:?} {:?}", key, value);
+///     }
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct DBFallibleIterator<'a> {
+    inner: DBIterator<'a>,
+    done: bool,
+}
+
+impl<'a> DBFallibleIterator<'a> {
+    /// See [`set_mode`](DBIterator::set_mode)
+    pub fn set_mode(&mut self, mode: IteratorMode) {
+        self.inner.set_mode(mode);
+        self.done = false;
+    }
+}
+
+impl<'a> Iterator for DBFallibleIterator<'a> {
+    type Item = Result<KVBytes, Error>;
+
+    fn next(&mut self) -> Option<Result<KVBytes, Error>> {
+        if self.done {
+            return None;
+        }
+
+        if let Some(item) = self.inner.next() {
+            Some(Ok(item))
+        } else {
+            self.done = true;
+            self.inner.status().err().map(Err)
+        }
+    }
+}
+
+/// An iterator which owns a reference to its database instead of borrowing it.
+///
+/// It is created by [`DB::iterator_owned`](struct.DB.html#method.iterator_owned) or by
+/// converting an [`OwnedSnapshot`](struct.OwnedSnapshot.html), and can be stored next to an
+/// `Arc<DB>` or moved into another thread.
+pub struct OwnedDBIterator {
+    // Fields are dropped in declaration order: the iterator must be released before the
+    // snapshot it reads from, and both before the database.
+    inner: DBIterator<'static>,
+    _snapshot: Option<OwnedSnapshot>,
+    _db: Arc<DB>,
+}
+
+impl OwnedDBIterator {
+    pub(crate) fn new(
+        inner: DBIterator<'static>,
+        snapshot: Option<OwnedSnapshot>,
+        db: Arc<DB>,
+    ) -> OwnedDBIterator {
+        OwnedDBIterator {
+            inner,
+            _snapshot: snapshot,
+            _db: db,
+        }
+    }
+
+    /// See [`DBIterator::set_mode`].
+    pub fn set_mode(&mut self, mode: IteratorMode) {
+        self.inner.set_mode(mode);
+    }
+
+    /// See [`DBIterator::valid`].
+    pub fn valid(&self) -> bool {
+        self.inner.valid()
+    }
+
+    /// See [`DBIterator::status`].
+    pub fn status(&self) -> Result<(), Error> {
+        self.inner.status()
+    }
+
+    /// See [`DBIterator::next_ref`].
+    pub fn next_ref(&mut self) -> Option<(&[u8], &[u8])> {
+        self.inner.next_ref()
+    }
+
+    /// Converts this iterator into an [`OwnedDBFallibleIterator`], see
+    /// [`DBIterator::into_fallible`].
+    pub fn into_fallible(self) -> OwnedDBFallibleIterator {
+        OwnedDBFallibleIterator {
+            inner: self,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for OwnedDBIterator {
+    type Item = KVBytes;
+
+    fn next(&mut self) -> Option<KVBytes> {
+        self.inner.next()
+    }
+}
+
+/// A [`DBFallibleIterator`] which owns a reference to its database instead of borrowing it.
+///
+/// It is created by [`OwnedDBIterator::into_fallible`].
+pub struct OwnedDBFallibleIterator {
+    inner: OwnedDBIterator,
+    done: bool,
+}
+
+impl OwnedDBFallibleIterator {
+    /// See [`DBIterator::set_mode`].
+    pub fn set_mode(&mut self, mode: IteratorMode) {
+        self.inner.set_mode(mode);
+        self.done = false;
+    }
+}
+
+impl Iterator for OwnedDBFallibleIterator {
+    type Item = Result<KVBytes, Error>;
+
+    fn next(&mut self) -> Option<Result<KVBytes, Error>> {
+        if self.done {
+            return None;
+        }
+
+        if let Some(item) = self.inner.next() {
+            Some(Ok(item))
+        } else {
+            self.done = true;
+            self.inner.status().err().map(Err)
+        }
+    }
+}
+
 /// Iterates the batches of writes since a given sequence number.
 ///
 /// `DBWALIterator` is returned by `DB::get_updates_since()` and will return the
@@ -516,6 +740,18 @@ impl DBWALIterator {
         }
         Ok(())
     }
+
+    /// Returns the write batch the iterator is positioned at, which is the one containing the
+    /// requested sequence number right after `get_updates_since()`. `next()` skips it.
+    pub(crate) fn current(&self) -> Option<(u64, WriteBatch)> {
+        if self.valid() {
+            let mut seq: u64 = 0;
+            let inner = unsafe { ffi::rocksdb_wal_iter_get_batch(self.inner, &mut seq) };
+            Some((seq, WriteBatch { inner }))
+        } else {
+            None
+        }
+    }
 }
 
 impl Iterator for DBWALIterator {
@@ -526,13 +762,7 @@ impl Iterator for DBWALIterator {
         unsafe {
             ffi::rocksdb_wal_iter_next(self.inner);
         }
-        if self.valid() {
-            let mut seq: u64 = 0;
-            let inner = unsafe { ffi::rocksdb_wal_iter_get_batch(self.inner, &mut seq) };
-            Some((seq, WriteBatch { inner }))
-        } else {
-            None
-        }
+        self.current()
     }
 }
 
@@ -14,6 +14,7 @@
 
 use std::ffi::{CStr, CString};
 use std::mem;
+use std::ops::{Bound, RangeBounds};
 use std::path::Path;
 
 use libc::{self, c_char, c_int, c_uchar, c_uint, c_void, size_t};
@@ -75,6 +76,67 @@ impl Drop for Cache {
     }
 }
 
+/// A rate limiter controlling the I/O rate of flushes and compactions.
+///
+/// Unlike `Options::set_ratelimiter`, which creates a new rate limiter for every
+/// DB opened with the options, a `RateLimiter` can be installed into several
+/// `Options` with `Options::set_rate_limiter`, so that all the DBs opened with them
+/// share a single I/O budget.
+///
+/// The limiter is created with RocksDB's generic rate limiter, which only limits
+/// writes and is not auto-tuned. The underlying limiter is reference counted, so
+/// the `RateLimiter` may be dropped while it is still used by opened DBs.
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{Options, RateLimiter};
+///
+/// let limiter = RateLimiter::new(10 * 1024 * 1024, 100 * 1000, 10).unwrap();
+///
+/// let mut opts1 = Options::default();
+/// opts1.set_rate_limiter(&limiter);
+///
+/// let mut opts2 = Options::default();
+/// opts2.set_rate_limiter(&limiter);
+/// ```
+pub struct RateLimiter {
+    pub(crate) inner: *mut ffi::rocksdb_ratelimiter_t,
+}
+
+impl RateLimiter {
+    /// Creates a generic rate limiter.
+    ///
+    /// * `rate_bytes_per_sec` - the total write rate allowed across all the users
+    ///   of the limiter, in bytes per second.
+    /// * `refill_period_us` - how often tokens are refilled, in microseconds.
+    ///   A typical value is 100 000 (100ms).
+    /// * `fairness` - the chance (1 out of `fairness`) that low-priority requests
+    ///   are served before pending high-priority ones. A typical value is 10.
+    pub fn new(
+        rate_bytes_per_sec: i64,
+        refill_period_us: i64,
+        fairness: i32,
+    ) -> Result<RateLimiter, Error> {
+        let limiter = unsafe {
+            ffi::rocksdb_ratelimiter_create(rate_bytes_per_sec, refill_period_us, fairness)
+        };
+        if limiter.is_null() {
+            Err(Error::new("Could not create rate limiter".to_owned()))
+        } else {
+            Ok(RateLimiter { inner: limiter })
+        }
+    }
+}
+
+impl Drop for RateLimiter {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::rocksdb_ratelimiter_destroy(self.inner);
+        }
+    }
+}
+
 /// An Env is an interface used by the rocksdb implementation to access
 /// operating system functionality like the filesystem etc.  Callers
 /// may wish to provide a custom Env object when opening a database to
@@ -327,6 +389,7 @@ unsafe impl Send for WriteOptions {}
 unsafe impl Send for BlockBasedOptions {}
 unsafe impl Send for ReadOptions {}
 unsafe impl Send for IngestExternalFileOptions {}
+unsafe impl Send for RateLimiter {}
 
 // Sync is similarly safe for many types because they do not expose interior mutability, and their
 // use within the rocksdb library is generally behind a const reference
@@ -335,6 +398,7 @@ unsafe impl Sync for WriteOptions {}
 unsafe impl Sync for BlockBasedOptions {}
 unsafe impl Sync for ReadOptions {}
 unsafe impl Sync for IngestExternalFileOptions {}
+unsafe impl Sync for RateLimiter {}
 
 impl Drop for Options {
     fn drop(&mut self) {
@@ -2536,6 +2600,28 @@ impl Options {
         }
     }
 
+    /// Uses the given rate limiter to control write rate of flush and compaction.
+    ///
+    /// In contrast to `set_ratelimiter`, the same limiter can be shared by several
+    /// `Options` (and therefore by several DBs).
+    ///
+    /// Default: disable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::{Options, RateLimiter};
+    ///
+    /// let limiter = RateLimiter::new(1024 * 1024, 100 * 1000, 10).unwrap();
+    /// let mut options = Options::default();
+    /// options.set_rate_limiter(&limiter);
+    /// ```
+    pub fn set_rate_limiter(&mut self, limiter: &RateLimiter) {
+        unsafe {
+            ffi::rocksdb_options_set_ratelimiter(self.inner, limiter.inner);
+        }
+    }
+
     /// Sets the maximal size of the info log file.
     ///
     /// If the log file is larger than `max_log_file_size`, a new info log file
@@ -2890,6 +2976,55 @@ impl ReadOptions {
         }
     }
 
+    /// Sets the lower and upper bounds for an iterator from a range of keys.
+    ///
+    /// RocksDB bounds are an inclusive lower bound and an exclusive upper bound, so an
+    /// excluded start or an included end of `range` is replaced by the key immediately
+    /// following it. This assumes the default bytewise comparator. Unbounded ends leave the
+    /// corresponding bound unchanged.
+    ///
+    /// Keys can be of any type implementing `AsRef<[u8]>`. Byte string literals have to be
+    /// sliced first, since a range of `&[u8; N]` is a range of both `[u8; N]` and `&[u8; N]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rocksdb::ReadOptions;
+    ///
+    /// let mut opts = ReadOptions::default();
+    /// opts.set_iterate_range(&b"a"[..]..=&b"c"[..]);
+    /// opts.set_iterate_range(b"a".to_vec()..);
+    /// ```
+    pub fn set_iterate_range<R, K>(&mut self, range: R)
+    where
+        R: RangeBounds<K>,
+        K: AsRef<[u8]> + ?Sized,
+    {
+        match range.start_bound() {
+            Bound::Included(key) => self.set_iterate_lower_bound(key.as_ref()),
+            Bound::Excluded(key) => self.set_iterate_lower_bound(key_successor(key.as_ref())),
+            Bound::Unbounded => {}
+        }
+        match range.end_bound() {
+            Bound::Included(key) => self.set_iterate_upper_bound(key_successor(key.as_ref())),
+            Bound::Excluded(key) => self.set_iterate_upper_bound(key.as_ref()),
+            Bound::Unbounded => {}
+        }
+    }
+
+    /// Sets the lower and upper bounds for an iterator to the keys starting with `prefix`.
+    ///
+    /// The upper bound is the smallest key greater than every key with that prefix, which
+    /// assumes the default bytewise comparator. A prefix made only of `0xff` bytes has no
+    /// such key, so only the lower bound is set then.
+    pub fn set_iterate_prefix<P: AsRef<[u8]>>(&mut self, prefix: P) {
+        let prefix = prefix.as_ref();
+        self.set_iterate_lower_bound(prefix);
+        if let Some(upper_bound) = prefix_successor(prefix) {
+            self.set_iterate_upper_bound(upper_bound);
+        }
+    }
+
     /// Specify if this read request should process data that ALREADY
     /// resides on a particular cache. If the required data is not
     /// found at the specified cache, then Status::Incomplete is returned.
@@ -3010,6 +3145,22 @@ impl ReadOptions {
     }
 }
 
+/// Returns the smallest key greater than `key` in bytewise order.
+fn key_successor(key: &[u8]) -> Vec<u8> {
+    let mut successor = Vec::with_capacity(key.len() + 1);
+    successor.extend_from_slice(key);
+    successor.push(0);
+    successor
+}
+
+/// Returns the smallest key greater than all keys starting with `prefix` in bytewise order.
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let last = prefix.iter().rposition(|&b| b != 0xff)?;
+    let mut successor = prefix[..=last].to_vec();
+    successor[last] += 1;
+    Some(successor)
+}
+
 impl Default for ReadOptions {
     fn default() -> ReadOptions {
         unsafe {
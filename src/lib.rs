@@ -87,30 +87,36 @@ mod db_options;
 mod db_pinnable_slice;
 pub mod merge_operator;
 pub mod perf;
+pub mod properties;
 mod slice_transform;
 mod snapshot;
 mod sst_file_writer;
+mod wal;
 mod write_batch;
 
 pub use crate::{
     column_family::{ColumnFamily, ColumnFamilyDescriptor, DEFAULT_COLUMN_FAMILY_NAME},
     compaction_filter::Decision as CompactionDecision,
     db::{LiveFile, DB},
-    db_iterator::{DBIterator, DBRawIterator, DBWALIterator, Direction, IteratorMode},
+    db_iterator::{
+        DBFallibleIterator, DBIterator, DBRawIterator, DBWALIterator, Direction, IteratorMode,
+        OwnedDBFallibleIterator, OwnedDBIterator,
+    },
     db_options::{
         BlockBasedIndexType, BlockBasedOptions, BottommostLevelCompaction, Cache, CompactOptions,
         DBCompactionStyle, DBCompressionType, DBPath, DBRecoveryMode, DataBlockIndexType, Env,
         FifoCompactOptions, FlushOptions, IngestExternalFileOptions, MemtableFactory, Options,
-        PlainTableFactoryOptions, ReadOptions, UniversalCompactOptions,
+        PlainTableFactoryOptions, RateLimiter, ReadOptions, ReadTier, UniversalCompactOptions,
         UniversalCompactionStopStyle, WriteOptions,
     },
     db_pinnable_slice::DBPinnableSlice,
     merge_operator::MergeOperands,
     perf::{PerfContext, PerfMetric, PerfStatsLevel},
     slice_transform::SliceTransform,
-    snapshot::Snapshot,
+    snapshot::{OwnedSnapshot, Snapshot},
     sst_file_writer::SstFileWriter,
-    write_batch::{WriteBatch, WriteBatchIterator},
+    wal::{WalOp, WalRecord, WalRecordIterator},
+    write_batch::{WriteBatch, WriteBatchHandler, WriteBatchIterator},
 };
 
 use librocksdb_sys as ffi;
@@ -162,8 +168,9 @@ impl fmt::Display for Error {
 #[cfg(test)]
 mod test {
     use super::{
-        BlockBasedOptions, ColumnFamily, ColumnFamilyDescriptor, DBIterator, DBRawIterator,
-        IngestExternalFileOptions, Options, PlainTableFactoryOptions, ReadOptions, Snapshot,
+        BlockBasedOptions, ColumnFamily, ColumnFamilyDescriptor, DBFallibleIterator, DBIterator,
+        DBRawIterator, IngestExternalFileOptions, Options, OwnedDBFallibleIterator,
+        OwnedDBIterator, OwnedSnapshot, PlainTableFactoryOptions, ReadOptions, Snapshot,
         SstFileWriter, WriteBatch, WriteOptions, DB,
     };
 
@@ -178,8 +185,12 @@ mod test {
 
         is_send::<DB>();
         is_send::<DBIterator<'_>>();
+        is_send::<DBFallibleIterator<'_>>();
         is_send::<DBRawIterator<'_>>();
         is_send::<Snapshot>();
+        is_send::<OwnedSnapshot>();
+        is_send::<OwnedDBIterator>();
+        is_send::<OwnedDBFallibleIterator>();
         is_send::<Options>();
         is_send::<ReadOptions>();
         is_send::<WriteOptions>();
@@ -0,0 +1,215 @@
+// Copyright 2020 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Names of the properties which can be retrieved with `DB::property_value` and
+//! `DB::property_int_value`, and their `_cf` variants.
+//!
+//! See `DB::Properties` in RocksDB's `db.h` for the description of each property.
+//!
+//! ```
+//! use rocksdb::{properties, DB, Options};
+//!
+//! let path = "_path_for_rocksdb_storage_properties";
+//! {
+//!     let db = DB::open_default(path).unwrap();
+//!     db.put(b"k1", b"v1").unwrap();
+//!     let keys = db.property_int_value(properties::ESTIMATE_NUM_KEYS).unwrap();
+//!     assert_eq!(keys, Some(1));
+//! }
+//! let _ = DB::destroy(&Options::default(), path);
+//! ```
+
+use crate::Error;
+
+/// Multi-line string of the statistics of the DB and all column families.
+pub const STATS: &str = "rocksdb.stats";
+/// Multi-line string summarizing the current SST files.
+pub const SSTABLES: &str = "rocksdb.sstables";
+/// Multi-line string of the statistics of a column family.
+///
+/// It is not parsed by this crate: the columns of its compaction stats table change between
+/// RocksDB releases, and sizes and key counts are rounded to human-readable strings.
+pub const CFSTATS: &str = "rocksdb.cfstats";
+/// Same as `CFSTATS`, without the file read latency histogram.
+pub const CFSTATS_NO_FILE_HISTOGRAM: &str = "rocksdb.cfstats-no-file-histogram";
+/// Multi-line string of the file read latency histogram of a column family.
+pub const CF_FILE_HISTOGRAM: &str = "rocksdb.cf-file-histogram";
+/// Multi-line string of the DB-level statistics.
+pub const DBSTATS: &str = "rocksdb.dbstats";
+/// Multi-line string with the number of files and size of each level, see `DB::level_stats`.
+pub const LEVELSTATS: &str = "rocksdb.levelstats";
+/// Number of immutable memtables that have not yet been flushed.
+pub const NUM_IMMUTABLE_MEM_TABLE: &str = "rocksdb.num-immutable-mem-table";
+/// Number of immutable memtables that have already been flushed.
+pub const NUM_IMMUTABLE_MEM_TABLE_FLUSHED: &str = "rocksdb.num-immutable-mem-table-flushed";
+/// 1 if a memtable flush is pending, otherwise 0.
+pub const MEM_TABLE_FLUSH_PENDING: &str = "rocksdb.mem-table-flush-pending";
+/// 1 if at least one compaction is pending, otherwise 0.
+pub const COMPACTION_PENDING: &str = "rocksdb.compaction-pending";
+/// Number of currently running compactions.
+pub const NUM_RUNNING_COMPACTIONS: &str = "rocksdb.num-running-compactions";
+/// Number of currently running flushes.
+pub const NUM_RUNNING_FLUSHES: &str = "rocksdb.num-running-flushes";
+/// Accumulated number of background errors.
+pub const BACKGROUND_ERRORS: &str = "rocksdb.background-errors";
+/// Approximate size of the active memtable, in bytes.
+pub const CUR_SIZE_ACTIVE_MEM_TABLE: &str = "rocksdb.cur-size-active-mem-table";
+/// Approximate size of the active and unflushed immutable memtables, in bytes.
+pub const CUR_SIZE_ALL_MEM_TABLES: &str = "rocksdb.cur-size-all-mem-tables";
+/// Approximate size of the active, unflushed immutable and pinned immutable memtables, in
+/// bytes.
+pub const SIZE_ALL_MEM_TABLES: &str = "rocksdb.size-all-mem-tables";
+/// Total number of entries in the active memtable.
+pub const NUM_ENTRIES_ACTIVE_MEM_TABLE: &str = "rocksdb.num-entries-active-mem-table";
+/// Total number of entries in the unflushed immutable memtables.
+pub const NUM_ENTRIES_IMM_MEM_TABLES: &str = "rocksdb.num-entries-imm-mem-tables";
+/// Total number of delete entries in the active memtable.
+pub const NUM_DELETES_ACTIVE_MEM_TABLE: &str = "rocksdb.num-deletes-active-mem-table";
+/// Total number of delete entries in the unflushed immutable memtables.
+pub const NUM_DELETES_IMM_MEM_TABLES: &str = "rocksdb.num-deletes-imm-mem-tables";
+/// Estimated number of total keys in the active and unflushed immutable memtables and
+/// storage.
+pub const ESTIMATE_NUM_KEYS: &str = "rocksdb.estimate-num-keys";
+/// Estimated memory used for reading SST tables, excluding the block cache, in bytes.
+pub const ESTIMATE_TABLE_READERS_MEM: &str = "rocksdb.estimate-table-readers-mem";
+/// 1 if deletion of obsolete files is enabled, otherwise 0.
+pub const IS_FILE_DELETIONS_ENABLED: &str = "rocksdb.is-file-deletions-enabled";
+/// Number of unreleased snapshots of the database.
+pub const NUM_SNAPSHOTS: &str = "rocksdb.num-snapshots";
+/// Unix timestamp of the oldest unreleased snapshot.
+pub const OLDEST_SNAPSHOT_TIME: &str = "rocksdb.oldest-snapshot-time";
+/// Sequence number of the oldest unreleased snapshot.
+pub const OLDEST_SNAPSHOT_SEQUENCE: &str = "rocksdb.oldest-snapshot-sequence";
+/// Number of live versions.
+pub const NUM_LIVE_VERSIONS: &str = "rocksdb.num-live-versions";
+/// Number of the current super version.
+pub const CURRENT_SUPER_VERSION_NUMBER: &str = "rocksdb.current-super-version-number";
+/// Estimated size of the live data, in bytes.
+pub const ESTIMATE_LIVE_DATA_SIZE: &str = "rocksdb.estimate-live-data-size";
+/// Minimum log number of the log files that should be kept.
+pub const MIN_LOG_NUMBER_TO_KEEP: &str = "rocksdb.min-log-number-to-keep";
+/// Minimum file number of the obsolete SST files that should be kept.
+pub const MIN_OBSOLETE_SST_NUMBER_TO_KEEP: &str = "rocksdb.min-obsolete-sst-number-to-keep";
+/// Total size of all SST files of all versions, in bytes.
+pub const TOTAL_SST_FILES_SIZE: &str = "rocksdb.total-sst-files-size";
+/// Total size of the SST files of the current version, in bytes.
+pub const LIVE_SST_FILES_SIZE: &str = "rocksdb.live-sst-files-size";
+/// Level to which L0 data will be compacted.
+pub const BASE_LEVEL: &str = "rocksdb.base-level";
+/// Estimated total number of bytes compaction needs to rewrite to get all levels down to
+/// under their target size.
+pub const ESTIMATE_PENDING_COMPACTION_BYTES: &str = "rocksdb.estimate-pending-compaction-bytes";
+/// String of the aggregated table properties of the column family.
+pub const AGGREGATED_TABLE_PROPERTIES: &str = "rocksdb.aggregated-table-properties";
+/// Current actual delayed write rate, 0 meaning no delay.
+pub const ACTUAL_DELAYED_WRITE_RATE: &str = "rocksdb.actual-delayed-write-rate";
+/// 1 if writes have been stopped, otherwise 0.
+pub const IS_WRITE_STOPPED: &str = "rocksdb.is-write-stopped";
+/// Estimated oldest key timestamp in the DB, only available for FIFO compaction.
+pub const ESTIMATE_OLDEST_KEY_TIME: &str = "rocksdb.estimate-oldest-key-time";
+/// Capacity of the block cache, in bytes.
+pub const BLOCK_CACHE_CAPACITY: &str = "rocksdb.block-cache-capacity";
+/// Memory size of the entries residing in the block cache, in bytes.
+pub const BLOCK_CACHE_USAGE: &str = "rocksdb.block-cache-usage";
+/// Memory size of the entries pinned in the block cache, in bytes.
+pub const BLOCK_CACHE_PINNED_USAGE: &str = "rocksdb.block-cache-pinned-usage";
+/// Multi-line string of the statistics collected when `Options::enable_statistics` is set.
+pub const OPTIONS_STATISTICS: &str = "rocksdb.options-statistics";
+
+/// Name of the property with the number of files at `level`.
+pub fn num_files_at_level(level: usize) -> String {
+    format!("rocksdb.num-files-at-level{}", level)
+}
+
+/// Name of the property with the compression ratio of the data at `level`, which is
+/// "-1.0" when there are no open files at that level.
+pub fn compression_ratio_at_level(level: usize) -> String {
+    format!("rocksdb.compression-ratio-at-level{}", level)
+}
+
+/// Name of the property with the aggregated table properties of the files at `level`.
+pub fn aggregated_table_properties_at_level(level: usize) -> String {
+    format!("rocksdb.aggregated-table-properties-at-level{}", level)
+}
+
+/// The number of files and size of a level, as reported by the `LEVELSTATS` property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelStats {
+    pub level: usize,
+    pub num_files: u64,
+    /// Size of the files of the level, in megabytes rounded to the nearest integer.
+    pub size_mb: u64,
+}
+
+/// Parses the value of the `LEVELSTATS` property.
+pub(crate) fn parse_level_stats(value: &str) -> Result<Vec<LevelStats>, Error> {
+    let invalid = || Error::new(format!("Invalid level stats: {:?}", value));
+    value
+        .lines()
+        .skip(2)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut fields = line.split_whitespace();
+            let mut next = || fields.next().ok_or_else(invalid);
+            Ok(LevelStats {
+                level: next()?.parse().map_err(|_| invalid())?,
+                num_files: next()?.parse().map_err(|_| invalid())?,
+                size_mb: next()?.parse().map_err(|_| invalid())?,
+            })
+        })
+        .collect()
+}
+
+/// The pending and running flushes and compactions, as reported by the corresponding
+/// properties, see `DB::background_work_stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackgroundWorkStats {
+    /// Number of flushes running in the database.
+    pub num_running_flushes: u64,
+    /// Number of compactions running in the database.
+    pub num_running_compactions: u64,
+    /// Whether a memtable flush of the column family is pending.
+    pub flush_pending: bool,
+    /// Whether at least one compaction of the column family is pending.
+    pub compaction_pending: bool,
+    /// Number of immutable memtables of the column family that have not yet been flushed.
+    pub num_immutable_mem_tables: u64,
+    /// Estimated number of bytes the compactions of the column family need to rewrite.
+    pub estimate_pending_compaction_bytes: u64,
+    /// Whether writes to the database are stopped.
+    pub is_write_stopped: bool,
+    /// Rate in bytes per second to which writes to the database are delayed, 0 meaning they
+    /// are not delayed.
+    pub actual_delayed_write_rate: u64,
+}
+
+/// Reads the properties of `BackgroundWorkStats` with `property_int_value`.
+pub(crate) fn background_work_stats(
+    property_int_value: impl Fn(&str) -> Result<Option<u64>, Error>,
+) -> Result<BackgroundWorkStats, Error> {
+    let value = |name: &str| {
+        property_int_value(name)?
+            .ok_or_else(|| Error::new(format!("Property {} is not available", name)))
+    };
+    Ok(BackgroundWorkStats {
+        num_running_flushes: value(NUM_RUNNING_FLUSHES)?,
+        num_running_compactions: value(NUM_RUNNING_COMPACTIONS)?,
+        flush_pending: value(MEM_TABLE_FLUSH_PENDING)? != 0,
+        compaction_pending: value(COMPACTION_PENDING)? != 0,
+        num_immutable_mem_tables: value(NUM_IMMUTABLE_MEM_TABLE)?,
+        estimate_pending_compaction_bytes: value(ESTIMATE_PENDING_COMPACTION_BYTES)?,
+        is_write_stopped: value(IS_WRITE_STOPPED)? != 0,
+        actual_delayed_write_rate: value(ACTUAL_DELAYED_WRITE_RATE)?,
+    })
+}
@@ -12,7 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{ffi, ColumnFamily, DBIterator, DBRawIterator, Error, IteratorMode, ReadOptions, DB};
+use crate::{
+    db::prefix_scan_opts, ffi, ColumnFamily, DBIterator, DBPinnableSlice, DBRawIterator, Direction,
+    Error, IteratorMode, OwnedDBIterator, ReadOptions, DB,
+};
+
+use std::ops::RangeBounds;
+use std::sync::Arc;
 
 /// A consistent view of the database at the point of creation.
 ///
@@ -76,6 +82,90 @@ impl<'a> Snapshot<'a> {
         DBIterator::new_cf(self.db, cf_handle, readopts, mode)
     }
 
+    /// Creates an iterator over the data in this snapshot, seeking to `prefix` with
+    /// `prefix_same_as_start` set.
+    ///
+    /// See [`DB::prefix_iterator`].
+    pub fn prefix_iterator<P: AsRef<[u8]>>(&self, prefix: P) -> DBIterator<'a> {
+        let mut readopts = ReadOptions::default();
+        readopts.set_prefix_same_as_start(true);
+        self.iterator_opt(
+            IteratorMode::From(prefix.as_ref(), Direction::Forward),
+            readopts,
+        )
+    }
+
+    /// Creates an iterator over the data in this snapshot under the given column family,
+    /// seeking to `prefix` with `prefix_same_as_start` set.
+    ///
+    /// See [`DB::prefix_iterator_cf`].
+    pub fn prefix_iterator_cf<P: AsRef<[u8]>>(
+        &self,
+        cf_handle: &ColumnFamily,
+        prefix: P,
+    ) -> DBIterator {
+        let mut readopts = ReadOptions::default();
+        readopts.set_prefix_same_as_start(true);
+        self.iterator_cf_opt(
+            cf_handle,
+            readopts,
+            IteratorMode::From(prefix.as_ref(), Direction::Forward),
+        )
+    }
+
+    /// Creates an iterator over exactly the keys starting with `prefix` in this snapshot, in
+    /// the given direction.
+    ///
+    /// See [`DB::prefix_scan`].
+    pub fn prefix_scan<P: AsRef<[u8]>>(&self, prefix: P, direction: Direction) -> DBIterator<'a> {
+        self.iterator_opt(direction.start_mode(), prefix_scan_opts(prefix))
+    }
+
+    /// Creates an iterator over exactly the keys starting with `prefix` in this snapshot under
+    /// the given column family, in the given direction.
+    ///
+    /// See [`DB::prefix_scan_cf`].
+    pub fn prefix_scan_cf<P: AsRef<[u8]>>(
+        &self,
+        cf_handle: &ColumnFamily,
+        prefix: P,
+        direction: Direction,
+    ) -> DBIterator {
+        self.iterator_cf_opt(cf_handle, prefix_scan_opts(prefix), direction.start_mode())
+    }
+
+    /// Creates an iterator over the keys in `range` in this snapshot, in the given direction.
+    ///
+    /// See [`DB::range_iterator`].
+    pub fn range_iterator<R, K>(&self, range: R, direction: Direction) -> DBIterator<'a>
+    where
+        R: RangeBounds<K>,
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_range(range);
+        self.iterator_opt(direction.start_mode(), readopts)
+    }
+
+    /// Creates an iterator over the keys in `range` in this snapshot under the given column
+    /// family, in the given direction.
+    ///
+    /// See [`DB::range_iterator_cf`].
+    pub fn range_iterator_cf<R, K>(
+        &self,
+        cf_handle: &ColumnFamily,
+        range: R,
+        direction: Direction,
+    ) -> DBIterator
+    where
+        R: RangeBounds<K>,
+        K: AsRef<[u8]> + ?Sized,
+    {
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_range(range);
+        self.iterator_cf_opt(cf_handle, readopts, direction.start_mode())
+    }
+
     /// Creates a raw iterator over the data in this snapshot, using the default read options.
     pub fn raw_iterator(&self) -> DBRawIterator {
         let readopts = ReadOptions::default();
@@ -143,6 +233,97 @@ impl<'a> Snapshot<'a> {
         readopts.set_snapshot(self);
         self.db.get_cf_opt(cf, key.as_ref(), &readopts)
     }
+
+    /// Returns the value associated with a key using RocksDB's PinnableSlice, with default read
+    /// options.
+    pub fn get_pinned<K: AsRef<[u8]>>(&self, key: K) -> Result<Option<DBPinnableSlice>, Error> {
+        let readopts = ReadOptions::default();
+        self.get_pinned_opt(key, readopts)
+    }
+
+    /// Returns the value associated with a key and given column family using RocksDB's
+    /// PinnableSlice, with default read options.
+    pub fn get_pinned_cf<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+    ) -> Result<Option<DBPinnableSlice>, Error> {
+        let readopts = ReadOptions::default();
+        self.get_pinned_cf_opt(cf, key, readopts)
+    }
+
+    /// Returns the value associated with a key using RocksDB's PinnableSlice, with the given
+    /// read options.
+    pub fn get_pinned_opt<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<DBPinnableSlice>, Error> {
+        readopts.set_snapshot(self);
+        self.db.get_pinned_opt(key, &readopts)
+    }
+
+    /// Returns the value associated with a key and given column family using RocksDB's
+    /// PinnableSlice, with the given read options.
+    pub fn get_pinned_cf_opt<K: AsRef<[u8]>>(
+        &self,
+        cf: &ColumnFamily,
+        key: K,
+        mut readopts: ReadOptions,
+    ) -> Result<Option<DBPinnableSlice>, Error> {
+        readopts.set_snapshot(self);
+        self.db.get_pinned_cf_opt(cf, key, &readopts)
+    }
+
+    /// Returns the values associated with the given keys, with default read options.
+    pub fn multi_get<K, I>(&self, keys: I) -> Result<Vec<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        let readopts = ReadOptions::default();
+        self.multi_get_opt(keys, readopts)
+    }
+
+    /// Returns the values associated with the given keys and column families, with default read
+    /// options.
+    pub fn multi_get_cf<'c, K, I>(&self, keys: I) -> Result<Vec<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        let readopts = ReadOptions::default();
+        self.multi_get_cf_opt(keys, readopts)
+    }
+
+    /// Returns the values associated with the given keys, with the given read options.
+    pub fn multi_get_opt<K, I>(
+        &self,
+        keys: I,
+        mut readopts: ReadOptions,
+    ) -> Result<Vec<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = K>,
+    {
+        readopts.set_snapshot(self);
+        self.db.multi_get_opt(keys, &readopts)
+    }
+
+    /// Returns the values associated with the given keys and column families, with the given
+    /// read options.
+    pub fn multi_get_cf_opt<'c, K, I>(
+        &self,
+        keys: I,
+        mut readopts: ReadOptions,
+    ) -> Result<Vec<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'c ColumnFamily, K)>,
+    {
+        readopts.set_snapshot(self);
+        self.db.multi_get_cf_opt(keys, &readopts)
+    }
 }
 
 impl<'a> Drop for Snapshot<'a> {
@@ -157,3 +338,97 @@ impl<'a> Drop for Snapshot<'a> {
 /// immutable and can be safely shared between threads.
 unsafe impl<'a> Send for Snapshot<'a> {}
 unsafe impl<'a> Sync for Snapshot<'a> {}
+
+/// A snapshot which owns a reference to its database instead of borrowing it.
+///
+/// This can be stored next to an `Arc<DB>` or moved into another thread. Reads go through the
+/// borrowed [`Snapshot`] returned by [`snapshot`](#method.snapshot).
+///
+/// # Examples
+///
+/// ```
+/// use rocksdb::{DB, IteratorMode, Options};
+/// use std::sync::Arc;
+///
+/// let path = "_path_for_rocksdb_storage_owned_snapshot";
+/// {
+///     let db = Arc::new(DB::open_default(path).unwrap());
+///     db.put(b"k1", b"v1").unwrap();
+///     let snapshot = db.snapshot_owned();
+///     db.put(b"k1", b"v2").unwrap();
+///
+///     let handle = std::thread::spawn(move || {
+///         assert_eq!(snapshot.snapshot().get(b"k1").unwrap().unwrap(), b"v1");
+///         snapshot.into_iterator(IteratorMode::Start).count()
+///     });
+///     assert_eq!(handle.join().unwrap(), 1);
+/// }
+/// let _ = DB::destroy(&Options::default(), path);
+/// ```
+pub struct OwnedSnapshot {
+    // Declared before `db` so the snapshot is released before the database can be dropped.
+    snapshot: Snapshot<'static>,
+    db: Arc<DB>,
+}
+
+impl OwnedSnapshot {
+    /// Creates a new `OwnedSnapshot` of the database `db`.
+    pub fn new(db: Arc<DB>) -> OwnedSnapshot {
+        // The `'static` lifetime never escapes: `snapshot` is only handed out borrowed from
+        // `self`, and `db` keeps the database alive for as long as it exists.
+        let db_ref: &'static DB = unsafe { &*Arc::as_ptr(&db) };
+        OwnedSnapshot {
+            snapshot: Snapshot::new(db_ref),
+            db,
+        }
+    }
+
+    /// Returns the database this snapshot was taken from.
+    pub fn db(&self) -> &Arc<DB> {
+        &self.db
+    }
+
+    /// Returns the snapshot, which can be used to read from the database.
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    /// Converts this snapshot into an iterator over its data, using the default read options.
+    pub fn into_iterator(self, mode: IteratorMode) -> OwnedDBIterator {
+        let readopts = ReadOptions::default();
+        self.into_iterator_opt(mode, readopts)
+    }
+
+    /// Converts this snapshot into an iterator over its data, using the given read options.
+    pub fn into_iterator_opt(
+        self,
+        mode: IteratorMode,
+        mut readopts: ReadOptions,
+    ) -> OwnedDBIterator {
+        readopts.set_snapshot(&self.snapshot);
+        let iter = DBIterator::new(&self.db, readopts, mode);
+        let db = Arc::clone(&self.db);
+        OwnedDBIterator::new(iter, Some(self), db)
+    }
+
+    /// Converts this snapshot into an iterator over its data under the given column family,
+    /// using the default read options.
+    pub fn into_iterator_cf(self, cf_handle: &ColumnFamily, mode: IteratorMode) -> OwnedDBIterator {
+        let readopts = ReadOptions::default();
+        self.into_iterator_cf_opt(cf_handle, readopts, mode)
+    }
+
+    /// Converts this snapshot into an iterator over its data under the given column family,
+    /// using the given read options.
+    pub fn into_iterator_cf_opt(
+        self,
+        cf_handle: &ColumnFamily,
+        mut readopts: ReadOptions,
+        mode: IteratorMode,
+    ) -> OwnedDBIterator {
+        readopts.set_snapshot(&self.snapshot);
+        let iter = DBIterator::new_cf(&self.db, cf_handle, readopts, mode);
+        let db = Arc::clone(&self.db);
+        OwnedDBIterator::new(iter, Some(self), db)
+    }
+}
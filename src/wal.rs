@@ -0,0 +1,321 @@
+// Copyright 2020 Tyler Neely
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{DBWALIterator, Error, WriteBatch, DB};
+use std::convert::TryInto;
+use std::thread;
+use std::time::Duration;
+
+// Record tags of the serialized write batch format, see `ValueType` in RocksDB's `dbformat.h`.
+const TYPE_DELETION: u8 = 0x0;
+const TYPE_VALUE: u8 = 0x1;
+const TYPE_MERGE: u8 = 0x2;
+const TYPE_LOG_DATA: u8 = 0x3;
+const TYPE_CF_DELETION: u8 = 0x4;
+const TYPE_CF_VALUE: u8 = 0x5;
+const TYPE_CF_MERGE: u8 = 0x6;
+const TYPE_SINGLE_DELETION: u8 = 0x7;
+const TYPE_CF_SINGLE_DELETION: u8 = 0x8;
+const TYPE_BEGIN_PREPARE_XID: u8 = 0x9;
+const TYPE_END_PREPARE_XID: u8 = 0xA;
+const TYPE_COMMIT_XID: u8 = 0xB;
+const TYPE_ROLLBACK_XID: u8 = 0xC;
+const TYPE_NOOP: u8 = 0xD;
+const TYPE_CF_RANGE_DELETION: u8 = 0xE;
+const TYPE_RANGE_DELETION: u8 = 0xF;
+const TYPE_BEGIN_PERSISTED_PREPARE_XID: u8 = 0x12;
+const TYPE_BEGIN_UNPREPARE_XID: u8 = 0x13;
+
+/// Size of the sequence number and count preceding the records of a write batch.
+pub(crate) const HEADER_SIZE: usize = 12;
+
+/// A single operation of a write batch, decoded from its serialized form.
+///
+/// `cf` is the id of the column family the operation applies to, `0` being the default column
+/// family.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalOp {
+    Put {
+        cf: u32,
+        key: Box<[u8]>,
+        value: Box<[u8]>,
+    },
+    Delete {
+        cf: u32,
+        key: Box<[u8]>,
+    },
+    SingleDelete {
+        cf: u32,
+        key: Box<[u8]>,
+    },
+    /// Deletes the keys in the range [`begin_key`, `end_key`).
+    DeleteRange {
+        cf: u32,
+        begin_key: Box<[u8]>,
+        end_key: Box<[u8]>,
+    },
+    Merge {
+        cf: u32,
+        key: Box<[u8]>,
+        value: Box<[u8]>,
+    },
+    /// A blob added with `WriteBatch::put_log_data`, which is only written to the WAL.
+    LogData {
+        blob: Box<[u8]>,
+    },
+}
+
+/// The decoded operations of a write batch read from the WAL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalRecord {
+    /// The sequence number of the first operation of the batch.
+    pub seq: u64,
+    /// The operations of the batch, in the order they were added.
+    pub ops: Vec<WalOp>,
+}
+
+impl WalRecord {
+    /// Decodes the operations of `batch`, which was written with sequence number `seq`.
+    ///
+    /// Transaction markers and no-ops are skipped. Blob indexes of BlobDB can't be decoded and
+    /// are reported as an error.
+    pub fn from_batch(seq: u64, batch: &WriteBatch) -> Result<WalRecord, Error> {
+        Ok(WalRecord {
+            seq,
+            ops: decode_ops(batch.data())?,
+        })
+    }
+}
+
+pub(crate) fn decode_ops(data: &[u8]) -> Result<Vec<WalOp>, Error> {
+    let mut ops = Vec::new();
+    for_each_op(data, |op| ops.push(op))?;
+    Ok(ops)
+}
+
+/// Decodes the records of the serialized write batch `data` one at a time, calling `f` with
+/// each operation as soon as it is decoded.
+///
+/// The operations preceding a record which can't be decoded are passed to `f` before the error
+/// is returned.
+pub(crate) fn for_each_op<F>(data: &[u8], mut f: F) -> Result<(), Error>
+where
+    F: FnMut(WalOp),
+{
+    if data.len() < HEADER_SIZE {
+        return Err(Error::new("Write batch is too small".to_owned()));
+    }
+    let mut input = Input(&data[HEADER_SIZE..]);
+    while !input.0.is_empty() {
+        let tag = input.byte()?;
+        let cf = match tag {
+            TYPE_CF_VALUE
+            | TYPE_CF_DELETION
+            | TYPE_CF_SINGLE_DELETION
+            | TYPE_CF_RANGE_DELETION
+            | TYPE_CF_MERGE => input.varint32()?,
+            _ => 0,
+        };
+        let op = match tag {
+            TYPE_VALUE | TYPE_CF_VALUE => WalOp::Put {
+                cf,
+                key: input.slice()?,
+                value: input.slice()?,
+            },
+            TYPE_DELETION | TYPE_CF_DELETION => WalOp::Delete {
+                cf,
+                key: input.slice()?,
+            },
+            TYPE_SINGLE_DELETION | TYPE_CF_SINGLE_DELETION => WalOp::SingleDelete {
+                cf,
+                key: input.slice()?,
+            },
+            TYPE_RANGE_DELETION | TYPE_CF_RANGE_DELETION => WalOp::DeleteRange {
+                cf,
+                begin_key: input.slice()?,
+                end_key: input.slice()?,
+            },
+            TYPE_MERGE | TYPE_CF_MERGE => WalOp::Merge {
+                cf,
+                key: input.slice()?,
+                value: input.slice()?,
+            },
+            TYPE_LOG_DATA => WalOp::LogData {
+                blob: input.slice()?,
+            },
+            TYPE_END_PREPARE_XID | TYPE_COMMIT_XID | TYPE_ROLLBACK_XID => {
+                input.slice()?;
+                continue;
+            }
+            TYPE_BEGIN_PREPARE_XID
+            | TYPE_BEGIN_PERSISTED_PREPARE_XID
+            | TYPE_BEGIN_UNPREPARE_XID
+            | TYPE_NOOP => continue,
+            _ => {
+                return Err(Error::new(format!(
+                    "Unknown write batch record tag: {:#x}",
+                    tag
+                )))
+            }
+        };
+        f(op);
+    }
+    Ok(())
+}
+
+/// The unread part of a serialized write batch.
+struct Input<'a>(&'a [u8]);
+
+impl<'a> Input<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < n {
+            return Err(Error::new("Write batch record is truncated".to_owned()));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint32(&mut self) -> Result<u32, Error> {
+        let mut value = 0_u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            value |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::new("Write batch varint is too long".to_owned()))
+    }
+
+    fn slice(&mut self) -> Result<Box<[u8]>, Error> {
+        let len = self.varint32()?;
+        let len = len
+            .try_into()
+            .map_err(|_| Error::new("Write batch record is too large".to_owned()))?;
+        Ok(self.take(len)?.into())
+    }
+}
+
+/// Iterates the decoded write batches written since a given sequence number.
+///
+/// `WalRecordIterator` is returned by `DB::wal_records_since()`, which ends once the latest
+/// write has been read, and by `DB::tail_wal_records_since()`, which instead waits for new
+/// writes.
+///
+/// The first record is the write batch containing the requested sequence number. Sequence
+/// numbers of writes which skipped the WAL, e.g. with `WriteOptions::disable_wal`, have no
+/// record and are skipped.
+///
+/// An error reading the WAL is returned as an item. It is the last item of an iterator
+/// returned by `wal_records_since()`, while a tailing iterator waits for `poll_interval`
+/// before trying again.
+pub struct WalRecordIterator<'a> {
+    db: &'a DB,
+    inner: Option<DBWALIterator>,
+    /// Whether the batch `inner` was positioned at when opened has been read.
+    started: bool,
+    /// Whether the last item was an error reading the WAL.
+    failed: bool,
+    next_seq: u64,
+    poll_interval: Option<Duration>,
+}
+
+impl<'a> WalRecordIterator<'a> {
+    pub(crate) fn new(
+        db: &'a DB,
+        inner: Option<DBWALIterator>,
+        seq: u64,
+        poll_interval: Option<Duration>,
+    ) -> WalRecordIterator<'a> {
+        WalRecordIterator {
+            db,
+            inner,
+            started: false,
+            failed: false,
+            // Sequence numbers start at 1.
+            next_seq: seq.max(1),
+            poll_interval,
+        }
+    }
+
+    /// Returns the sequence number following the last operation read. Passing it to
+    /// `DB::wal_records_since()` resumes reading after that operation.
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    fn next_batch(&mut self) -> Result<Option<(u64, WriteBatch)>, Error> {
+        if self.failed {
+            // Retrying right away would most likely fail the same way.
+            match self.poll_interval {
+                Some(poll_interval) => thread::sleep(poll_interval),
+                None => return Ok(None),
+            }
+            self.failed = false;
+        }
+        loop {
+            if let Some(inner) = &mut self.inner {
+                let first = !self.started;
+                self.started = true;
+                let batch = if first { inner.current() } else { inner.next() };
+                if let Some((seq, batch)) = batch {
+                    self.next_seq = self.next_seq.max(seq + batch.len() as u64);
+                    return Ok(Some((seq, batch)));
+                }
+                let status = inner.status();
+                self.inner = None;
+                if first {
+                    // Nothing was written at `next_seq` or after it yet.
+                    status?;
+                    match self.poll_interval {
+                        Some(poll_interval) => thread::sleep(poll_interval),
+                        None => return Ok(None),
+                    }
+                }
+                // Otherwise the iterator stopped at the end of the WAL or at a gap left by
+                // writes which skipped it, reporting TryAgain or Corruption respectively. A
+                // new iterator opened at `next_seq` reads past either.
+            }
+            if self.db.latest_sequence_number() < self.next_seq {
+                match self.poll_interval {
+                    Some(poll_interval) => thread::sleep(poll_interval),
+                    None => return Ok(None),
+                }
+                continue;
+            }
+            self.inner = Some(self.db.get_updates_since(self.next_seq)?);
+            self.started = false;
+        }
+    }
+}
+
+impl<'a> Iterator for WalRecordIterator<'a> {
+    type Item = Result<WalRecord, Error>;
+
+    fn next(&mut self) -> Option<Result<WalRecord, Error>> {
+        match self.next_batch() {
+            Ok(Some((seq, batch))) => Some(WalRecord::from_batch(seq, &batch)),
+            Ok(None) => None,
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
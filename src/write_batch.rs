@@ -12,8 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{ffi, ColumnFamily};
-use libc::{c_char, c_void, size_t};
+use crate::{
+    ffi,
+    wal::{for_each_op, HEADER_SIZE},
+    ColumnFamily, Error, WalOp,
+};
+use libc::{c_char, c_int, c_void, size_t};
 use std::slice;
 
 /// An atomic batch of write operations.
@@ -49,6 +53,27 @@ pub trait WriteBatchIterator {
     fn delete(&mut self, key: Box<[u8]>);
 }
 
+/// Receives all the operations of a write batch, with their column family ids.
+///
+/// Unlike `WriteBatchIterator`, every method has a default implementation which ignores the
+/// operation, so only the operations of interest need to be handled. The column family id is
+/// `0` for the default column family, and other ids are assigned in order of creation.
+#[allow(clippy::boxed_local)]
+pub trait WriteBatchHandler {
+    /// Called with a key and value that were `put` into the batch.
+    fn put_cf(&mut self, _cf: u32, _key: Box<[u8]>, _value: Box<[u8]>) {}
+    /// Called with a key that was `delete`d from the batch.
+    fn delete_cf(&mut self, _cf: u32, _key: Box<[u8]>) {}
+    /// Called with a key that was `single_delete`d from the batch.
+    fn single_delete_cf(&mut self, _cf: u32, _key: Box<[u8]>) {}
+    /// Called with the range of keys [`begin_key`, `end_key`) that was deleted from the batch.
+    fn delete_range_cf(&mut self, _cf: u32, _begin_key: Box<[u8]>, _end_key: Box<[u8]>) {}
+    /// Called with a key and value that were `merge`d into the batch.
+    fn merge_cf(&mut self, _cf: u32, _key: Box<[u8]>, _value: Box<[u8]>) {}
+    /// Called with a blob that was added with `put_log_data`.
+    fn log_data(&mut self, _blob: Box<[u8]>) {}
+}
+
 unsafe extern "C" fn writebatch_put_callback(
     state: *mut c_void,
     k: *const c_char,
@@ -78,6 +103,21 @@ unsafe extern "C" fn writebatch_delete_callback(state: *mut c_void, k: *const c_
 }
 
 impl WriteBatch {
+    /// Creates a batch from its serialized representation, as returned by `data()`.
+    ///
+    /// Only the header of `data` is checked here, so that batches with records of any type
+    /// can be loaded. Invalid records are reported when the batch is written or iterated.
+    pub fn from_data(data: &[u8]) -> Result<WriteBatch, Error> {
+        if data.len() < HEADER_SIZE {
+            return Err(Error::new("Write batch is too small".to_owned()));
+        }
+        Ok(WriteBatch {
+            inner: unsafe {
+                ffi::rocksdb_writebatch_create_from(data.as_ptr() as *const c_char, data.len())
+            },
+        })
+    }
+
     pub fn len(&self) -> usize {
         unsafe { ffi::rocksdb_writebatch_count(self.inner) as usize }
     }
@@ -91,10 +131,42 @@ impl WriteBatch {
         }
     }
 
+    /// Returns the serialized representation of this batch, as written to the WAL.
+    ///
+    /// Use [`WalRecord::from_batch`](struct.WalRecord.html#method.from_batch) to decode it.
+    pub fn data(&self) -> &[u8] {
+        unsafe {
+            let mut size: size_t = 0;
+            let data = ffi::rocksdb_writebatch_data(self.inner, &mut size);
+            slice::from_raw_parts(data as *const u8, size as usize)
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Appends the operations of `other` to this batch.
+    ///
+    /// The batch is rebuilt from the concatenated operations, so save points set on it
+    /// are discarded: `rollback_to_save_point` and `pop_save_point` return an error until a
+    /// new save point is set.
+    pub fn append(&mut self, other: &WriteBatch) {
+        if other.data().len() <= HEADER_SIZE {
+            return;
+        }
+        let mut data = self.data().to_vec();
+        data.extend_from_slice(&other.data()[HEADER_SIZE..]);
+        let count = (self.len() + other.len()) as u32;
+        data[8..HEADER_SIZE].copy_from_slice(&count.to_le_bytes());
+        let batch = WriteBatch {
+            inner: unsafe {
+                ffi::rocksdb_writebatch_create_from(data.as_ptr() as *const c_char, data.len())
+            },
+        };
+        *self = batch;
+    }
+
     /// Iterate the put and delete operations within this write batch. Note that
     /// this does _not_ return an `Iterator` but instead will invoke the `put()`
     /// and `delete()` member functions of the provided `WriteBatchIterator`
@@ -114,6 +186,27 @@ impl WriteBatch {
         }
     }
 
+    /// Iterate all the operations within this write batch, invoking the member
+    /// function of the provided `WriteBatchHandler` matching each of them.
+    ///
+    /// The records are decoded one at a time and passed on as soon as they are decoded. An
+    /// error is returned if the batch contains a record which can't be decoded, after the
+    /// operations preceding it have been passed to `handler`.
+    pub fn iterate_all(&self, handler: &mut dyn WriteBatchHandler) -> Result<(), Error> {
+        for_each_op(self.data(), |op| match op {
+            WalOp::Put { cf, key, value } => handler.put_cf(cf, key, value),
+            WalOp::Delete { cf, key } => handler.delete_cf(cf, key),
+            WalOp::SingleDelete { cf, key } => handler.single_delete_cf(cf, key),
+            WalOp::DeleteRange {
+                cf,
+                begin_key,
+                end_key,
+            } => handler.delete_range_cf(cf, begin_key, end_key),
+            WalOp::Merge { cf, key, value } => handler.merge_cf(cf, key, value),
+            WalOp::LogData { blob } => handler.log_data(blob),
+        })
+    }
+
     /// Insert a value into the database under the given key.
     pub fn put<K, V>(&mut self, key: K, value: V)
     where
@@ -193,6 +286,107 @@ impl WriteBatch {
         }
     }
 
+    /// Insert a value into the database under the given key, where the key and the value
+    /// are each the concatenation of the given parts.
+    ///
+    /// The parts are copied into the batch directly, without first being concatenated.
+    pub fn putv(&mut self, key: &[&[u8]], value: &[&[u8]]) {
+        let (key, value) = (SliceParts::new(key), SliceParts::new(value));
+
+        unsafe {
+            ffi::rocksdb_writebatch_putv(
+                self.inner,
+                key.len(),
+                key.ptrs.as_ptr(),
+                key.sizes.as_ptr(),
+                value.len(),
+                value.ptrs.as_ptr(),
+                value.sizes.as_ptr(),
+            );
+        }
+    }
+
+    pub fn putv_cf(&mut self, cf: &ColumnFamily, key: &[&[u8]], value: &[&[u8]]) {
+        let (key, value) = (SliceParts::new(key), SliceParts::new(value));
+
+        unsafe {
+            ffi::rocksdb_writebatch_putv_cf(
+                self.inner,
+                cf.inner,
+                key.len(),
+                key.ptrs.as_ptr(),
+                key.sizes.as_ptr(),
+                value.len(),
+                value.ptrs.as_ptr(),
+                value.sizes.as_ptr(),
+            );
+        }
+    }
+
+    /// Merge a value into the database under the given key, where the key and the value
+    /// are each the concatenation of the given parts.
+    pub fn mergev(&mut self, key: &[&[u8]], value: &[&[u8]]) {
+        let (key, value) = (SliceParts::new(key), SliceParts::new(value));
+
+        unsafe {
+            ffi::rocksdb_writebatch_mergev(
+                self.inner,
+                key.len(),
+                key.ptrs.as_ptr(),
+                key.sizes.as_ptr(),
+                value.len(),
+                value.ptrs.as_ptr(),
+                value.sizes.as_ptr(),
+            );
+        }
+    }
+
+    pub fn mergev_cf(&mut self, cf: &ColumnFamily, key: &[&[u8]], value: &[&[u8]]) {
+        let (key, value) = (SliceParts::new(key), SliceParts::new(value));
+
+        unsafe {
+            ffi::rocksdb_writebatch_mergev_cf(
+                self.inner,
+                cf.inner,
+                key.len(),
+                key.ptrs.as_ptr(),
+                key.sizes.as_ptr(),
+                value.len(),
+                value.ptrs.as_ptr(),
+                value.sizes.as_ptr(),
+            );
+        }
+    }
+
+    /// Removes the database entry for the key which is the concatenation of the given parts.
+    /// Does nothing if the key was not found.
+    pub fn deletev(&mut self, key: &[&[u8]]) {
+        let key = SliceParts::new(key);
+
+        unsafe {
+            ffi::rocksdb_writebatch_deletev(
+                self.inner,
+                key.len(),
+                key.ptrs.as_ptr(),
+                key.sizes.as_ptr(),
+            );
+        }
+    }
+
+    pub fn deletev_cf(&mut self, cf: &ColumnFamily, key: &[&[u8]]) {
+        let key = SliceParts::new(key);
+
+        unsafe {
+            ffi::rocksdb_writebatch_deletev_cf(
+                self.inner,
+                cf.inner,
+                key.len(),
+                key.ptrs.as_ptr(),
+                key.sizes.as_ptr(),
+            );
+        }
+    }
+
     /// Removes the database entry for key. Does nothing if the key was not found.
     pub fn delete<K: AsRef<[u8]>>(&mut self, key: K) {
         let key = key.as_ref();
@@ -219,6 +413,35 @@ impl WriteBatch {
         }
     }
 
+    /// Removes the database entry for key, which must have been put only once and not
+    /// overwritten since. Does nothing if the key was not found.
+    ///
+    /// See RocksDB's `SingleDelete` for the restrictions which apply.
+    pub fn single_delete<K: AsRef<[u8]>>(&mut self, key: K) {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_singledelete(
+                self.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        }
+    }
+
+    pub fn single_delete_cf<K: AsRef<[u8]>>(&mut self, cf: &ColumnFamily, key: K) {
+        let key = key.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_singledelete_cf(
+                self.inner,
+                cf.inner,
+                key.as_ptr() as *const c_char,
+                key.len() as size_t,
+            );
+        }
+    }
+
     /// Remove database entries from start key to end key.
     ///
     /// Removes the database entries in the range ["begin_key", "end_key"), i.e.,
@@ -264,6 +487,74 @@ impl WriteBatch {
             ffi::rocksdb_writebatch_clear(self.inner);
         }
     }
+
+    /// Append a blob of arbitrary size to the records in this batch.
+    ///
+    /// The blob is only written to the WAL, where it can be read back when iterating the
+    /// batch, and is not applied to the database. It doesn't take a sequence number and
+    /// isn't counted in `len()`.
+    pub fn put_log_data<B: AsRef<[u8]>>(&mut self, blob: B) {
+        let blob = blob.as_ref();
+
+        unsafe {
+            ffi::rocksdb_writebatch_put_log_data(
+                self.inner,
+                blob.as_ptr() as *const c_char,
+                blob.len() as size_t,
+            );
+        }
+    }
+
+    /// Records the state of the batch for future calls to `rollback_to_save_point()`.
+    /// May be called multiple times to set multiple save points.
+    pub fn set_save_point(&mut self) {
+        unsafe {
+            ffi::rocksdb_writebatch_set_save_point(self.inner);
+        }
+    }
+
+    /// Removes all entries in this batch added since the most recent call to
+    /// `set_save_point()`, and removes that save point.
+    ///
+    /// Returns an error if there is no previous save point.
+    pub fn rollback_to_save_point(&mut self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_writebatch_rollback_to_save_point(self.inner));
+        }
+        Ok(())
+    }
+
+    /// Removes the most recent save point without rolling back the entries added since.
+    ///
+    /// Returns an error if there is no previous save point.
+    pub fn pop_save_point(&mut self) -> Result<(), Error> {
+        unsafe {
+            ffi_try!(ffi::rocksdb_writebatch_pop_save_point(self.inner));
+        }
+        Ok(())
+    }
+}
+
+/// The pointers and sizes of the parts of a key or value, as taken by RocksDB's `SliceParts`.
+struct SliceParts {
+    ptrs: Vec<*const c_char>,
+    sizes: Vec<size_t>,
+}
+
+impl SliceParts {
+    fn new(parts: &[&[u8]]) -> SliceParts {
+        SliceParts {
+            ptrs: parts
+                .iter()
+                .map(|part| part.as_ptr() as *const c_char)
+                .collect(),
+            sizes: parts.iter().map(|part| part.len() as size_t).collect(),
+        }
+    }
+
+    fn len(&self) -> c_int {
+        self.ptrs.len() as c_int
+    }
 }
 
 impl Default for WriteBatch {
@@ -16,7 +16,7 @@ mod util;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{checkpoint::Checkpoint, Options, DB};
+use rocksdb::{checkpoint::Checkpoint, IteratorMode, Options, DB};
 use util::DBPath;
 
 #[test]
@@ -99,3 +99,56 @@ pub fn test_multi_checkpoints() {
     assert_eq!(cp.get(b"k5").unwrap().unwrap(), b"v5");
     assert_eq!(cp.get(b"k6").unwrap().unwrap(), b"v6");
 }
+
+fn keys(db: &DB) -> Vec<Vec<u8>> {
+    db.iterator(IteratorMode::Start)
+        .map(|(key, _)| key.to_vec())
+        .collect()
+}
+
+#[test]
+pub fn test_replay_wal_into_checkpoint() {
+    const PATH_PREFIX: &str = "_rust_rocksdb_cp_replay_";
+
+    let db_path = DBPath::new(&format!("{}db1", PATH_PREFIX));
+
+    // Keep archived WAL files around so that they can be replayed
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_ttl_seconds(1000);
+    let db = DB::open(&opts, &db_path).unwrap();
+
+    let cp0_path = DBPath::new(&format!("{}cp0", PATH_PREFIX));
+    Checkpoint::new(&db)
+        .unwrap()
+        .create_checkpoint(&cp0_path)
+        .unwrap();
+
+    db.put(b"k1", b"v1").unwrap();
+
+    let cp1_path = DBPath::new(&format!("{}cp1", PATH_PREFIX));
+    Checkpoint::new(&db)
+        .unwrap()
+        .create_checkpoint(&cp1_path)
+        .unwrap();
+
+    db.put(b"k2", b"v2").unwrap();
+    db.delete(b"k1").unwrap();
+    db.put(b"k3", b"v3").unwrap();
+    assert_eq!(db.latest_sequence_number(), 4);
+
+    // Replaying into a checkpoint taken before any write starts with the first batch
+    let cp0 = DB::open_default(&cp0_path).unwrap();
+    assert_eq!(cp0.latest_sequence_number(), 0);
+    assert_eq!(db.replay_wal_into(&cp0, 1).unwrap(), 1);
+    assert_eq!(keys(&cp0), vec![b"k1".to_vec()]);
+    assert_eq!(db.replay_wal_into(&cp0, 10).unwrap(), 4);
+    assert_eq!(keys(&cp0), vec![b"k2".to_vec(), b"k3".to_vec()]);
+
+    let cp1 = DB::open_default(&cp1_path).unwrap();
+    assert_eq!(cp1.latest_sequence_number(), 1);
+    assert_eq!(db.replay_wal_into(&cp1, 3).unwrap(), 3);
+    assert_eq!(keys(&cp1), vec![b"k2".to_vec()]);
+    assert_eq!(db.replay_wal_into(&cp1, 10).unwrap(), 4);
+    assert_eq!(keys(&cp1), vec![b"k2".to_vec(), b"k3".to_vec()]);
+}
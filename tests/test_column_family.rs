@@ -14,6 +14,8 @@
 
 mod util;
 
+use std::{thread, time::Duration};
+
 use pretty_assertions::assert_eq;
 
 use rocksdb::{ColumnFamilyDescriptor, MergeOperands, Options, DB, DEFAULT_COLUMN_FAMILY_NAME};
@@ -250,3 +252,61 @@ fn test_create_duplicate_column_family() {
         assert!(db.create_cf("cf1", &opts).is_err());
     }
 }
+
+#[test]
+fn test_column_family_with_ttl() {
+    let n = DBPath::new("_rust_rocksdb_column_family_with_ttl");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let short = ColumnFamilyDescriptor::new_with_ttl(
+            "short",
+            Options::default(),
+            Duration::from_secs(1),
+        );
+        let long = ColumnFamilyDescriptor::new("long", Options::default());
+
+        let mut db =
+            DB::open_cf_descriptors_with_ttl(&opts, &n, vec![short, long], Duration::from_secs(0))
+                .unwrap();
+        db.create_cf_with_ttl("created", &Options::default(), Duration::from_secs(1))
+            .unwrap();
+
+        for name in &["short", "long", "created"] {
+            let cf = db.cf_handle(name).unwrap();
+            db.put_cf(cf, b"key1", b"value1").unwrap();
+        }
+
+        thread::sleep(Duration::from_secs(2));
+        // Trigger a manual compaction, this will check the TTL filter
+        // in the database and drop all expired entries.
+        for name in &["short", "long", "created"] {
+            let cf = db.cf_handle(name).unwrap();
+            db.compact_range_cf(cf, None::<&[u8]>, None::<&[u8]>);
+        }
+
+        let short = db.cf_handle("short").unwrap();
+        let long = db.cf_handle("long").unwrap();
+        let created = db.cf_handle("created").unwrap();
+        assert!(db.get_cf(short, b"key1").unwrap().is_none());
+        assert_eq!(db.get_cf(long, b"key1").unwrap().unwrap(), b"value1");
+        assert!(db.get_cf(created, b"key1").unwrap().is_none());
+    }
+}
+
+#[test]
+fn test_create_column_family_with_ttl_requires_ttl_db() {
+    let n = DBPath::new("_rust_rocksdb_create_column_family_with_ttl_requires_ttl_db");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+
+        let mut db = DB::open(&opts, &n).unwrap();
+        assert!(db
+            .create_cf_with_ttl("cf1", &Options::default(), Duration::from_secs(1))
+            .is_err());
+        assert!(db.cf_handle("cf1").is_none());
+    }
+}
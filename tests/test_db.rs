@@ -14,15 +14,20 @@
 
 mod util;
 
-use std::{mem, sync::Arc, thread, time::Duration};
+use std::{
+    fs, mem,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
 
 use pretty_assertions::assert_eq;
 
 use rocksdb::{
-    perf::get_memory_usage_stats, BlockBasedOptions, BottommostLevelCompaction, Cache,
+    perf::get_memory_usage_stats, properties, BlockBasedOptions, BottommostLevelCompaction, Cache,
     CompactOptions, DBCompactionStyle, Env, Error, FifoCompactOptions, IteratorMode, Options,
     PerfContext, PerfMetric, ReadOptions, SliceTransform, Snapshot, UniversalCompactOptions,
-    UniversalCompactionStopStyle, WriteBatch, DB,
+    UniversalCompactionStopStyle, WalOp, WalRecord, WriteBatch, WriteOptions, DB,
 };
 use util::DBPath;
 
@@ -262,6 +267,45 @@ fn snapshot_test() {
     }
 }
 
+#[test]
+fn snapshot_read_paths_test() {
+    let path = DBPath::new("_rust_rocksdb_snapshot_read_paths_test");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(2));
+        let db = DB::open_cf(&opts, &path, &["cf"]).unwrap();
+        let cf = db.cf_handle("cf").unwrap();
+
+        db.put(b"k1", b"v1").unwrap();
+        db.put_cf(cf, b"k1", b"cf1").unwrap();
+        let snap = db.snapshot();
+        db.put(b"k1", b"v2").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        db.put_cf(cf, b"k1", b"cf2").unwrap();
+
+        assert_eq!(snap.get_pinned(b"k1").unwrap().unwrap().as_ref(), b"v1");
+        assert!(snap.get_pinned(b"k2").unwrap().is_none());
+        assert_eq!(
+            snap.get_pinned_cf(cf, b"k1").unwrap().unwrap().as_ref(),
+            b"cf1"
+        );
+        assert_eq!(
+            snap.multi_get(&[b"k1", b"k2"]).unwrap(),
+            vec![b"v1".to_vec(), vec![]]
+        );
+        assert_eq!(
+            snap.multi_get_cf(vec![(cf, b"k1")]).unwrap(),
+            vec![b"cf1".to_vec()]
+        );
+        assert_eq!(db.prefix_iterator(b"k2").count(), 1);
+        assert_eq!(snap.prefix_iterator(b"k2").count(), 0);
+        let (_, value) = snap.prefix_iterator_cf(cf, b"k1").next().unwrap();
+        assert_eq!(&*value, b"cf1");
+    }
+}
+
 #[derive(Clone)]
 struct SnapshotWrapper {
     snapshot: Arc<Snapshot<'static>>,
@@ -299,6 +343,86 @@ fn sync_snapshot_test() {
     assert!(handler_2.join().unwrap());
 }
 
+#[test]
+fn owned_snapshot_test() {
+    let path = DBPath::new("_rust_rocksdb_owned_snapshottest");
+    {
+        let db = Arc::new(DB::open_default(&path).unwrap());
+
+        assert!(db.put(b"k1", b"v1").is_ok());
+        let snapshot = db.snapshot_owned();
+        let iter = db.iterator_owned(IteratorMode::Start);
+        assert!(db.put(b"k1", b"v2").is_ok());
+        assert!(db.put(b"k2", b"v2").is_ok());
+
+        let handler_1 = thread::spawn(move || {
+            assert_eq!(snapshot.snapshot().get(b"k1").unwrap().unwrap(), b"v1");
+            assert!(snapshot.snapshot().get(b"k2").unwrap().is_none());
+            snapshot
+                .into_iterator(IteratorMode::End)
+                .map(|(k, v)| (k.to_vec(), v.to_vec()))
+                .collect::<Vec<_>>()
+        });
+        let handler_2 = thread::spawn(move || iter.count());
+
+        assert_eq!(
+            handler_1.join().unwrap(),
+            vec![(b"k1".to_vec(), b"v1".to_vec())]
+        );
+        assert_eq!(handler_2.join().unwrap(), 1);
+        assert_eq!(Arc::strong_count(&db), 1);
+    }
+}
+
+#[test]
+fn owned_cf_iterator_test() {
+    let path = DBPath::new("_rust_rocksdb_owned_cf_iteratortest");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let db = Arc::new(DB::open_cf(&opts, &path, &["cf"]).unwrap());
+        let cf = db.cf_handle("cf").unwrap();
+        for key in &[b"k1", b"k2", b"k3"] {
+            db.put_cf(cf, key, b"v1").unwrap();
+        }
+
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_upper_bound(b"k3".to_vec());
+        let iter = db.iterator_cf_opt_owned(cf, readopts, IteratorMode::Start);
+        let snapshot = db.snapshot_owned();
+        db.put_cf(cf, b"k0", b"v2").unwrap();
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_lower_bound(b"k1".to_vec());
+        let snapshot_iter = snapshot.into_iterator_cf_opt(cf, readopts, IteratorMode::End);
+
+        let handler_1 = thread::spawn(move || {
+            iter.into_fallible()
+                .map(|item| item.map(|(k, _)| k.to_vec()))
+                .collect::<Result<Vec<_>, Error>>()
+        });
+        let handler_2 = thread::spawn(move || {
+            snapshot_iter
+                .map(|(k, v)| (k.to_vec(), v.to_vec()))
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(
+            handler_1.join().unwrap().unwrap(),
+            vec![b"k1".to_vec(), b"k2".to_vec()]
+        );
+        assert_eq!(
+            handler_2.join().unwrap(),
+            vec![
+                (b"k3".to_vec(), b"v1".to_vec()),
+                (b"k2".to_vec(), b"v1".to_vec()),
+                (b"k1".to_vec(), b"v1".to_vec())
+            ]
+        );
+        assert_eq!(Arc::strong_count(&db), 1);
+    }
+}
+
 #[test]
 fn set_option_test() {
     let path = DBPath::new("_rust_rocksdb_set_optionstest");
@@ -402,6 +526,35 @@ impl rocksdb::WriteBatchIterator for OperationCounts {
     }
 }
 
+#[test]
+fn key_may_exist_test() {
+    let path = DBPath::new("_rust_rocksdb_key_may_exist_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(&opts, &path, &["cf"]).unwrap();
+    let cf = db.cf_handle("cf").unwrap();
+
+    assert!(!db.key_may_exist(b"k1"));
+    db.put(b"k1", b"v1").unwrap();
+    db.put_cf(cf, b"k2", b"v2").unwrap();
+
+    assert!(db.key_may_exist(b"k1"));
+    assert_eq!(
+        db.key_may_exist_opt(b"k1", &ReadOptions::default()),
+        (true, Some(b"v1".to_vec()))
+    );
+    assert!(!db.key_may_exist_cf(cf, b"k1"));
+    assert_eq!(
+        db.key_may_exist_cf_opt(cf, b"k2", &ReadOptions::default()),
+        (true, Some(b"v2".to_vec()))
+    );
+
+    // Once flushed, the value can't be read without IO
+    db.flush().unwrap();
+    assert!(db.key_may_exist(b"k1"));
+}
+
 #[test]
 fn test_get_updates_since_empty() {
     let path = DBPath::new("_rust_rocksdb_test_get_updates_since_empty");
@@ -468,6 +621,172 @@ fn test_get_updates_since_one_batch() {
     assert_eq!(counts.deletes, 1);
 }
 
+#[test]
+fn test_wal_records_since() {
+    let path = DBPath::new("_rust_rocksdb_test_wal_records_since");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(&opts, &path, &["cf"]).unwrap();
+    let cf = db.cf_handle("cf").unwrap();
+
+    db.put(b"key1", b"value1").unwrap();
+    let seq1 = db.latest_sequence_number();
+    let mut batch = WriteBatch::default();
+    batch.put_cf(cf, b"key2", b"value2");
+    batch.delete(b"key1");
+    batch.delete_range_cf(cf, b"a", b"b");
+    db.write(batch).unwrap();
+    db.put(b"key3", b"value3").unwrap();
+
+    let records = db
+        .wal_records_since(seq1)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    // Column family ids are assigned in order of creation, after the default one.
+    assert_eq!(
+        records,
+        vec![
+            WalRecord {
+                seq: 1,
+                ops: vec![WalOp::Put {
+                    cf: 0,
+                    key: b"key1".to_vec().into_boxed_slice(),
+                    value: b"value1".to_vec().into_boxed_slice(),
+                }],
+            },
+            WalRecord {
+                seq: 2,
+                ops: vec![
+                    WalOp::Put {
+                        cf: 1,
+                        key: b"key2".to_vec().into_boxed_slice(),
+                        value: b"value2".to_vec().into_boxed_slice(),
+                    },
+                    WalOp::Delete {
+                        cf: 0,
+                        key: b"key1".to_vec().into_boxed_slice(),
+                    },
+                    WalOp::DeleteRange {
+                        cf: 1,
+                        begin_key: b"a".to_vec().into_boxed_slice(),
+                        end_key: b"b".to_vec().into_boxed_slice(),
+                    },
+                ],
+            },
+            WalRecord {
+                seq: 5,
+                ops: vec![WalOp::Put {
+                    cf: 0,
+                    key: b"key3".to_vec().into_boxed_slice(),
+                    value: b"value3".to_vec().into_boxed_slice(),
+                }],
+            },
+        ]
+    );
+
+    let seqs: Vec<u64> = db
+        .wal_records_since(0)
+        .unwrap()
+        .map(|record| record.unwrap().seq)
+        .collect();
+    assert_eq!(seqs, vec![1, 2, 5]);
+
+    let mut batch = WriteBatch::default();
+    batch.merge_cf(cf, b"key4", b"value4");
+    assert_eq!(
+        WalRecord::from_batch(7, &batch).unwrap().ops,
+        vec![WalOp::Merge {
+            cf: 1,
+            key: b"key4".to_vec().into_boxed_slice(),
+            value: b"value4".to_vec().into_boxed_slice(),
+        }]
+    );
+}
+
+#[test]
+fn test_tail_wal_records_since() {
+    let path = DBPath::new("_rust_rocksdb_test_tail_wal_records_since");
+    let db = Arc::new(DB::open_default(&path).unwrap());
+    db.put(b"key1", b"value1").unwrap();
+
+    let writer = {
+        let db = Arc::clone(&db);
+        thread::spawn(move || {
+            for i in 2..5 {
+                thread::sleep(Duration::from_millis(50));
+                db.put(format!("key{}", i), b"value").unwrap();
+            }
+        })
+    };
+
+    let mut records = db.tail_wal_records_since(1, Duration::from_millis(10));
+    let seqs: Vec<u64> = records.by_ref().take(4).map(|r| r.unwrap().seq).collect();
+    assert_eq!(seqs, vec![1, 2, 3, 4]);
+    assert_eq!(records.next_seq(), 5);
+    writer.join().unwrap();
+}
+
+#[test]
+fn test_tail_wal_records_since_skips_unlogged_writes() {
+    let path = DBPath::new("_rust_rocksdb_test_tail_wal_records_since_skips_unlogged_writes");
+    let db = Arc::new(DB::open_default(&path).unwrap());
+    let mut write_opts = WriteOptions::default();
+    write_opts.disable_wal(true);
+    db.put(b"key1", b"value1").unwrap();
+    db.put_opt(b"key2", b"value2", &write_opts).unwrap();
+
+    let writer = {
+        let db = Arc::clone(&db);
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            db.put(b"key3", b"value3").unwrap();
+        })
+    };
+
+    // Sequence number 2 has no WAL record, which RocksDB reports as TryAgain once the
+    // iterator reaches it.
+    let mut records = db.tail_wal_records_since(1, Duration::from_millis(10));
+    let seqs: Vec<u64> = records.by_ref().take(2).map(|r| r.unwrap().seq).collect();
+    assert_eq!(seqs, vec![1, 3]);
+    assert_eq!(records.next_seq(), 4);
+    writer.join().unwrap();
+
+    let seqs: Vec<u64> = db
+        .wal_records_since(0)
+        .unwrap()
+        .map(|record| record.unwrap().seq)
+        .collect();
+    assert_eq!(seqs, vec![1, 3]);
+}
+
+#[test]
+fn test_tail_wal_records_since_unavailable_wal() {
+    let path = DBPath::new("_rust_rocksdb_test_tail_wal_records_since_unavailable_wal");
+    let wal_dir = (&path).as_ref().join("wal");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.set_wal_dir(&wal_dir);
+    let db = DB::open(&opts, &path).unwrap();
+    db.put(b"key1", b"value1").unwrap();
+    fs::remove_dir_all(&wal_dir).unwrap();
+
+    // Listing the WAL files fails from now on. The iterator keeps returning the error, but
+    // waits for the poll interval before each retry.
+    let poll_interval = Duration::from_millis(50);
+    let start = Instant::now();
+    let results: Vec<_> = db
+        .tail_wal_records_since(1, poll_interval)
+        .take(3)
+        .collect();
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(Result::is_err));
+    assert!(start.elapsed() >= poll_interval * 2);
+
+    assert!(db.wal_records_since(1).is_err());
+}
+
 #[test]
 fn test_get_updates_since_nothing() {
     let path = DBPath::new("_rust_rocksdb_test_get_updates_since_nothing");
@@ -915,3 +1234,66 @@ fn multi_get_cf() {
         assert_eq!(values[2], b"v2");
     }
 }
+
+#[test]
+fn close_test() {
+    let path = DBPath::new("_rust_rocksdb_close_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    {
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        db.put(b"k0", b"v0").unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+        db.put_cf(cf1, b"k1", b"v1").unwrap();
+        db.close().unwrap();
+    }
+
+    // Dropping the database would have left the writes in the WAL only, while closing it
+    // flushed each column family to an SST file.
+    let sst_files = fs::read_dir(&path)
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("sst".as_ref()))
+        .count();
+    assert_eq!(sst_files, 2);
+
+    {
+        // The flush fails on a read-only database, which `close` reports.
+        let db = DB::open_cf_for_read_only(&opts, &path, &["cf1"], false).unwrap();
+        assert!(db.close().is_err());
+    }
+    {
+        let db = DB::open_cf(&opts, &path, &["cf1"]).unwrap();
+        assert_eq!(db.get(b"k0").unwrap().unwrap(), b"v0");
+        let cf1 = db.cf_handle("cf1").unwrap();
+        assert_eq!(db.get_cf(cf1, b"k1").unwrap().unwrap(), b"v1");
+    }
+}
+
+#[test]
+fn file_deletions_test() {
+    let path = DBPath::new("_rust_rocksdb_file_deletions_test");
+    {
+        let db = DB::open_default(&path).unwrap();
+        let enabled = || {
+            db.property_int_value(properties::IS_FILE_DELETIONS_ENABLED)
+                .unwrap()
+                .unwrap()
+                == 1
+        };
+        assert!(enabled());
+
+        db.disable_file_deletions().unwrap();
+        db.disable_file_deletions().unwrap();
+        assert!(!enabled());
+        db.enable_file_deletions(false).unwrap();
+        assert!(!enabled());
+        db.enable_file_deletions(false).unwrap();
+        assert!(enabled());
+
+        db.disable_file_deletions().unwrap();
+        db.disable_file_deletions().unwrap();
+        db.enable_file_deletions(true).unwrap();
+        assert!(enabled());
+    }
+}
@@ -14,6 +14,11 @@
 
 mod util;
 
+use std::{
+    fs,
+    ops::{Bound, ControlFlow, RangeBounds},
+};
+
 use pretty_assertions::assert_eq;
 
 use rocksdb::{Direction, IteratorMode, MemtableFactory, Options, DB};
@@ -254,6 +259,65 @@ fn test_prefix_iterator_uses_full_prefix() {
     }
 }
 
+#[test]
+fn test_prefix_scan() {
+    let path = DBPath::new("_rust_rocksdb_prefix_scan_test");
+    {
+        let data: [(&[u8], &[u8]); 6] = [
+            (&[0, 1, 0], b"111"),
+            (&[0, 1, 1], b"222"),
+            (&[0, 1, 1, 0xff], b"333"),
+            (&[0, 1, 2], b"444"),
+            (&[0, 2], b"555"),
+            (&[0xff, 0xff], b"666"),
+        ];
+
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        opts.set_prefix_extractor(rocksdb::SliceTransform::create_fixed_prefix(1));
+
+        let db = DB::open_cf(&opts, &path, &["cf"]).unwrap();
+        let cf = db.cf_handle("cf").unwrap();
+        for (key, value) in &data {
+            db.put(key, value).unwrap();
+            db.put_cf(cf, key, value).unwrap();
+        }
+        let values = |iter: rocksdb::DBIterator| -> Vec<String> {
+            iter.map(|(_, v)| std::str::from_utf8(&v).unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(
+            values(db.prefix_scan(&[0, 1], Direction::Forward)),
+            vec!["111", "222", "333", "444"]
+        );
+        assert_eq!(
+            values(db.prefix_scan(&[0, 1, 1], Direction::Reverse)),
+            vec!["333", "222"]
+        );
+        assert_eq!(
+            values(db.prefix_scan(&[0xff], Direction::Reverse)),
+            vec!["666"]
+        );
+        assert_eq!(
+            values(db.prefix_scan_cf(cf, &[0, 1], Direction::Reverse)),
+            vec!["444", "333", "222", "111"]
+        );
+
+        let snapshot = db.snapshot();
+        db.put(&[0, 1, 3], b"777").unwrap();
+        assert_eq!(
+            values(snapshot.prefix_scan(&[0, 1], Direction::Reverse)),
+            vec!["444", "333", "222", "111"]
+        );
+        assert_eq!(
+            values(snapshot.prefix_scan_cf(cf, &[0, 2], Direction::Forward)),
+            vec!["555"]
+        );
+    }
+}
+
 #[test]
 fn test_full_iterator() {
     let path = DBPath::new("full_iterator_test");
@@ -321,3 +385,158 @@ fn test_iterator_outlive_db() {
     let t = trybuild::TestCases::new();
     t.compile_fail("tests/fail/iterator_outlive_db.rs");
 }
+
+#[test]
+fn test_fallible_iterator() {
+    let n = DBPath::new("_rust_rocksdb_fallible_iterator_test");
+    {
+        let db = DB::open_default(&n).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+
+        let items: Vec<_> = db
+            .iterator(IteratorMode::Start)
+            .into_fallible()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            items,
+            vec![(cba(b"k1"), cba(b"v1")), (cba(b"k2"), cba(b"v2"))]
+        );
+    }
+}
+
+#[test]
+fn test_fallible_iterator_reports_corruption() {
+    let n = DBPath::new("_rust_rocksdb_fallible_iterator_corruption_test");
+    {
+        let db = DB::open_default(&n).unwrap();
+        for i in 0..1000 {
+            db.put(format!("key{:04}", i), format!("value{:04}", i))
+                .unwrap();
+        }
+        db.flush().unwrap();
+    }
+
+    // Damage the first data block of the table file.
+    let sst = fs::read_dir(&n)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().map_or(false, |ext| ext == "sst"))
+        .unwrap();
+    let mut data = fs::read(&sst).unwrap();
+    for byte in &mut data[10..60] {
+        *byte = !*byte;
+    }
+    fs::write(&sst, data).unwrap();
+
+    {
+        let db = DB::open_default(&n).unwrap();
+
+        let mut iter = db.iterator(IteratorMode::Start);
+        assert!(iter.next().is_none());
+        assert!(iter.status().is_err());
+
+        let mut iter = db.iterator(IteratorMode::Start).into_fallible();
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+}
+
+#[test]
+fn test_iterator_next_ref() {
+    let n = DBPath::new("_rust_rocksdb_iterator_next_ref_test");
+    {
+        let db = DB::open_default(&n).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.put(b"k2", b"v2").unwrap();
+        db.put(b"k3", b"v3").unwrap();
+
+        let mut iter = db.iterator(IteratorMode::End);
+        assert_eq!(iter.next_ref(), Some((&b"k3"[..], &b"v3"[..])));
+        assert_eq!(iter.next(), Some((cba(b"k2"), cba(b"v2"))));
+        assert_eq!(iter.next_ref(), Some((&b"k1"[..], &b"v1"[..])));
+        assert_eq!(iter.next_ref(), None);
+
+        let mut seen = Vec::new();
+        db.iterator(IteratorMode::Start)
+            .for_each_ref(|key, value| {
+                seen.push((key.to_vec(), value.to_vec()));
+                if key == b"k2" {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .unwrap();
+        assert_eq!(
+            seen,
+            vec![
+                (b"k1".to_vec(), b"v1".to_vec()),
+                (b"k2".to_vec(), b"v2".to_vec())
+            ]
+        );
+    }
+}
+
+fn range_keys<R, K>(db: &DB, range: R, direction: Direction) -> Vec<Vec<u8>>
+where
+    R: RangeBounds<K>,
+    K: AsRef<[u8]> + ?Sized,
+{
+    db.range_iterator(range, direction)
+        .map(|(key, _)| key.to_vec())
+        .collect()
+}
+
+#[test]
+fn test_range_iterator() {
+    let n = DBPath::new("_rust_rocksdb_range_iterator_test");
+    {
+        let db = DB::open_default(&n).unwrap();
+        for key in &[&b"k1"[..], b"k2", b"k2\0", b"k3", b"k4", b"k5"] {
+            db.put(key, b"v").unwrap();
+        }
+        let k = |key: &[u8]| key.to_vec();
+        let (k2, k3, k4): (&[u8], &[u8], &[u8]) = (b"k2", b"k3", b"k4");
+
+        assert_eq!(
+            range_keys(&db, k2..k4, Direction::Forward),
+            vec![k(b"k2"), k(b"k2\0"), k(b"k3")]
+        );
+        assert_eq!(
+            range_keys(&db, k2..k4, Direction::Reverse),
+            vec![k(b"k3"), k(b"k2\0"), k(b"k2")]
+        );
+        assert_eq!(
+            range_keys(&db, k2..=k4, Direction::Reverse),
+            vec![k(b"k4"), k(b"k3"), k(b"k2\0"), k(b"k2")]
+        );
+        assert_eq!(
+            range_keys(&db, k3.., Direction::Forward),
+            vec![k(b"k3"), k(b"k4"), k(b"k5")]
+        );
+        assert_eq!(
+            range_keys(&db, ..k3, Direction::Reverse),
+            vec![k(b"k2\0"), k(b"k2"), k(b"k1")]
+        );
+        assert_eq!(
+            range_keys(&db, b"k2".to_vec()..=b"k2".to_vec(), Direction::Forward),
+            vec![k(b"k2")]
+        );
+
+        let excluded_start = (Bound::Excluded(b"k2".to_vec()), Bound::Unbounded);
+        assert_eq!(
+            range_keys(&db, excluded_start, Direction::Forward),
+            vec![k(b"k2\0"), k(b"k3"), k(b"k4"), k(b"k5")]
+        );
+
+        let snapshot = db.snapshot();
+        db.put(b"k35", b"v").unwrap();
+        let in_snapshot: Vec<_> = snapshot
+            .range_iterator(k3..k4, Direction::Forward)
+            .map(|(key, _)| key.to_vec())
+            .collect();
+        assert_eq!(in_snapshot, vec![k(b"k3")]);
+    }
+}
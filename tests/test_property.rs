@@ -16,7 +16,7 @@ mod util;
 
 use pretty_assertions::assert_eq;
 
-use rocksdb::{Options, DB};
+use rocksdb::{properties, Options, DB};
 use util::DBPath;
 
 #[test]
@@ -72,3 +72,81 @@ fn property_int_cf_test() {
         assert_eq!(total_keys, Some(0));
     }
 }
+
+#[test]
+fn property_constants_test() {
+    let n = DBPath::new("_rust_rocksdb_property_constants_test");
+    {
+        let db = DB::open_default(&n).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+
+        assert_eq!(
+            db.property_int_value(properties::ESTIMATE_NUM_KEYS)
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            db.property_int_value(properties::IS_WRITE_STOPPED).unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            db.property_int_value(&properties::num_files_at_level(0))
+                .unwrap(),
+            Some(0)
+        );
+    }
+}
+
+#[test]
+fn level_stats_test() {
+    let n = DBPath::new("_rust_rocksdb_level_stats_test");
+    {
+        let opts = Options::default();
+        let mut db = DB::open_default(&n).unwrap();
+        db.create_cf("cf1", &opts).unwrap();
+        db.put(b"k1", b"v1").unwrap();
+        db.flush().unwrap();
+
+        let stats = db.level_stats().unwrap().unwrap();
+        assert_eq!(stats.len(), 7);
+        assert_eq!(stats[0].level, 0);
+        assert_eq!(stats[0].num_files, 1);
+        assert_eq!(stats[0].size_mb, 0);
+        assert!(stats[1..].iter().all(|level| level.num_files == 0));
+
+        let cf = db.cf_handle("cf1").unwrap();
+        let stats = db.level_stats_cf(cf).unwrap().unwrap();
+        assert!(stats.iter().all(|level| level.num_files == 0));
+    }
+}
+
+#[test]
+fn background_work_stats_test() {
+    let n = DBPath::new("_rust_rocksdb_background_work_stats_test");
+    {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_disable_auto_compactions(true);
+        opts.set_level_zero_file_num_compaction_trigger(2);
+        let mut db = DB::open(&opts, &n).unwrap();
+        db.create_cf("cf1", &opts).unwrap();
+
+        let stats = db.background_work_stats().unwrap();
+        assert_eq!(stats.num_running_flushes, 0);
+        assert!(!stats.flush_pending);
+        assert!(!stats.compaction_pending);
+        assert!(!stats.is_write_stopped);
+
+        // Two L0 files reach the compaction trigger, but auto compactions are disabled.
+        for i in 0..2 {
+            db.put(format!("k{}", i), b"v").unwrap();
+            db.flush().unwrap();
+        }
+        assert!(db.background_work_stats().unwrap().compaction_pending);
+
+        let cf = db.cf_handle("cf1").unwrap();
+        let stats = db.background_work_stats_cf(cf).unwrap();
+        assert!(!stats.compaction_pending);
+        assert_eq!(stats.num_immutable_mem_tables, 0);
+    }
+}
@@ -16,7 +16,7 @@ mod util;
 
 use std::{fs, io::Read as _};
 
-use rocksdb::{BlockBasedOptions, DataBlockIndexType, Options, ReadOptions, DB};
+use rocksdb::{BlockBasedOptions, DataBlockIndexType, Options, RateLimiter, ReadOptions, DB};
 use util::DBPath;
 
 #[test]
@@ -134,3 +134,27 @@ fn test_set_data_block_index_type() {
         assert!(settings.contains("data_block_hash_table_util_ratio: 0.350000"));
     }
 }
+
+#[test]
+fn test_shared_rate_limiter() {
+    let n1 = DBPath::new("_rust_rocksdb_test_shared_rate_limiter_1");
+    let n2 = DBPath::new("_rust_rocksdb_test_shared_rate_limiter_2");
+    {
+        let limiter = RateLimiter::new(1024 * 1024, 100 * 1000, 10).unwrap();
+
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_rate_limiter(&limiter);
+
+        // The limiter is reference counted by RocksDB, so it may go away before the DBs.
+        drop(limiter);
+
+        let db1 = DB::open(&opts, &n1).unwrap();
+        let db2 = DB::open(&opts, &n2).unwrap();
+        for db in &[db1, db2] {
+            db.put(b"k1", b"v1").unwrap();
+            db.flush().unwrap();
+            assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+        }
+    }
+}
@@ -12,9 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod util;
+
 use pretty_assertions::assert_eq;
 
-use rocksdb::WriteBatch;
+use rocksdb::{Options, WalOp, WalRecord, WriteBatch, WriteBatchHandler, DB};
+use util::DBPath;
+
+/// Size of the sequence number and count preceding the records of a serialized batch.
+const HEADER_SIZE: usize = 12;
 
 #[test]
 fn test_write_batch_clear() {
@@ -25,3 +31,165 @@ fn test_write_batch_clear() {
     assert_eq!(batch.len(), 0);
     assert!(batch.is_empty());
 }
+
+#[derive(Default)]
+struct Recorder {
+    ops: Vec<String>,
+}
+
+impl WriteBatchHandler for Recorder {
+    fn put_cf(&mut self, cf: u32, key: Box<[u8]>, value: Box<[u8]>) {
+        self.ops.push(format!("put {} {:?} {:?}", cf, key, value));
+    }
+
+    fn merge_cf(&mut self, cf: u32, key: Box<[u8]>, value: Box<[u8]>) {
+        self.ops.push(format!("merge {} {:?} {:?}", cf, key, value));
+    }
+
+    fn delete_range_cf(&mut self, cf: u32, begin_key: Box<[u8]>, end_key: Box<[u8]>) {
+        self.ops
+            .push(format!("delete_range {} {:?} {:?}", cf, begin_key, end_key));
+    }
+}
+
+#[test]
+fn test_write_batch_iterate_all() {
+    let path = DBPath::new("_rust_rocksdb_write_batch_iterate_all_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(&opts, &path, &["cf"]).unwrap();
+    let cf = db.cf_handle("cf").unwrap();
+
+    let mut batch = WriteBatch::default();
+    batch.put(b"k1", b"v1");
+    batch.merge_cf(cf, b"k2", b"v2");
+    batch.delete(b"k1");
+    batch.delete_range_cf(cf, b"a", b"b");
+
+    let mut recorder = Recorder::default();
+    batch.iterate_all(&mut recorder).unwrap();
+    assert_eq!(
+        recorder.ops,
+        vec![
+            "put 0 [107, 49] [118, 49]",
+            "merge 1 [107, 50] [118, 50]",
+            "delete_range 1 [97] [98]",
+        ]
+    );
+
+    // The put is handled before the blob index record following it fails to decode.
+    let mut data = vec![0; HEADER_SIZE];
+    data[8] = 2;
+    data.extend_from_slice(b"\x01\x01k\x01v\x11\x01k\x04blob");
+    let mut recorder = Recorder::default();
+    assert!(WriteBatch::from_data(&data)
+        .unwrap()
+        .iterate_all(&mut recorder)
+        .is_err());
+    assert_eq!(recorder.ops, vec!["put 0 [107] [118]"]);
+}
+
+#[test]
+fn test_write_batch_data_and_append() {
+    let mut batch = WriteBatch::default();
+    batch.put(b"k1", b"v1");
+    batch.single_delete(b"k0");
+    batch.put_log_data(b"blob");
+    assert_eq!(batch.len(), 2);
+
+    let copy = WriteBatch::from_data(batch.data()).unwrap();
+    assert_eq!(copy.data(), batch.data());
+    assert!(WriteBatch::from_data(b"short").is_err());
+
+    // A blob index record of BlobDB, which can't be decoded but is still loaded.
+    let mut blob_index = vec![0; HEADER_SIZE];
+    blob_index[8] = 1;
+    blob_index.extend_from_slice(b"\x11\x01k\x04blob");
+    assert_eq!(WriteBatch::from_data(&blob_index).unwrap().len(), 1);
+
+    let mut other = WriteBatch::default();
+    other.delete(b"k1");
+    other.put(b"k2", b"v2");
+    batch.set_save_point();
+    batch.append(&other);
+    assert_eq!(batch.len(), 4);
+    assert_eq!(
+        WalRecord::from_batch(0, &batch).unwrap().ops,
+        vec![
+            WalOp::Put {
+                cf: 0,
+                key: Box::from(&b"k1"[..]),
+                value: Box::from(&b"v1"[..]),
+            },
+            WalOp::SingleDelete {
+                cf: 0,
+                key: Box::from(&b"k0"[..]),
+            },
+            WalOp::LogData {
+                blob: Box::from(&b"blob"[..]),
+            },
+            WalOp::Delete {
+                cf: 0,
+                key: Box::from(&b"k1"[..]),
+            },
+            WalOp::Put {
+                cf: 0,
+                key: Box::from(&b"k2"[..]),
+                value: Box::from(&b"v2"[..]),
+            },
+        ]
+    );
+    // The batch is rebuilt by append, which discards its save points.
+    assert!(batch.rollback_to_save_point().is_err());
+}
+
+#[test]
+fn test_write_batch_save_points() {
+    let path = DBPath::new("_rust_rocksdb_write_batch_save_points_test");
+    let db = DB::open_default(&path).unwrap();
+
+    let mut batch = WriteBatch::default();
+    assert!(batch.rollback_to_save_point().is_err());
+    batch.put(b"k1", b"v1");
+    batch.set_save_point();
+    batch.put(b"k2", b"v2");
+    batch.set_save_point();
+    batch.put(b"k3", b"v3");
+    batch.pop_save_point().unwrap();
+    batch.rollback_to_save_point().unwrap();
+    assert!(batch.pop_save_point().is_err());
+    assert_eq!(batch.len(), 1);
+    db.write(batch).unwrap();
+
+    assert_eq!(db.get(b"k1").unwrap().unwrap(), b"v1");
+    assert!(db.get(b"k2").unwrap().is_none());
+    assert!(db.get(b"k3").unwrap().is_none());
+}
+
+#[test]
+fn test_write_batch_slice_parts() {
+    let path = DBPath::new("_rust_rocksdb_write_batch_slice_parts_test");
+    let mut opts = Options::default();
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(&opts, &path, &["cf"]).unwrap();
+    let cf = db.cf_handle("cf").unwrap();
+
+    let mut batch = WriteBatch::default();
+    batch.putv(&[&b"tenant1"[..], b":", b"k1"], &[&b"v"[..], b"1"]);
+    batch.putv_cf(cf, &[&b"tenant1"[..], b":", b"k2"], &[&b"v2"[..]]);
+    batch.putv(&[&b"tenant1:"[..], b"k3"], &[]);
+    db.write(batch).unwrap();
+    assert_eq!(db.get(b"tenant1:k1").unwrap().unwrap(), b"v1");
+    assert_eq!(db.get_cf(cf, b"tenant1:k2").unwrap().unwrap(), b"v2");
+    assert_eq!(db.get(b"tenant1:k3").unwrap().unwrap(), b"");
+
+    db.deletev(&[&b"tenant1:"[..], b"k1"]).unwrap();
+    db.deletev_cf(cf, &[&b"tenant1"[..], b":k2"]).unwrap();
+    db.putv(&[&b"tenant2:"[..], b"k1"], &[&b"v"[..], b"3"])
+        .unwrap();
+    assert!(db.get(b"tenant1:k1").unwrap().is_none());
+    assert!(db.get_cf(cf, b"tenant1:k2").unwrap().is_none());
+    assert_eq!(db.get(b"tenant2:k1").unwrap().unwrap(), b"v3");
+}